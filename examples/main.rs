@@ -2,7 +2,7 @@ use iwgui::*;
 
 use log::LevelFilter;
 use simple_logger::SimpleLogger;
-use std::{thread, time::Duration};
+use std::time::Duration;
 
 struct Duck {
     name: String,
@@ -51,7 +51,15 @@ fn main() {
     let mut model = Model::example();
     loop {
         for connection in &mut server.connections() {
-            let mut gui = connection.gui();
+            // Open the page with `?debug` to see what is sent
+            let debug = connection.query_param("debug").is_some();
+            connection.set_debug_overlay(debug);
+            // ... and with `?dark` to see the dark theme
+            if connection.query_param("dark").is_some() {
+                connection.set_theme(Theme::dark());
+            }
+            let gui = connection.gui();
+            gui.title("Ducks and Paper Planes");
             let root = gui.root();
             let (left, right) = root.vertical_panels();
 
@@ -61,16 +69,21 @@ fn main() {
             // Build the right side of the GUI
             paper_planes(right, &mut model.paper_planes);
 
-            connection.show_gui(gui);
+            connection.show_gui(gui).unwrap();
         }
-        thread::sleep(Duration::from_millis(50));
+        server.wait_for_events(Duration::from_secs(1));
     }
 }
 
 fn ducks(left: Indeterminate, ducks_at_the_pont: &mut Vec<Duck>) {
     let mut stack = left.stacklayout();
     stack.header("Ducks at the Pont".to_owned());
-    if stack.button().text("Wave arms").finish() {
+    if stack
+        .button()
+        .text("Wave arms")
+        .tooltip("Greets the ducks")
+        .finish()
+    {
         println!("Waving arms like a lunatic");
     }
     for duck in ducks_at_the_pont {
@@ -102,7 +115,12 @@ fn ducks(left: Indeterminate, ducks_at_the_pont: &mut Vec<Duck>) {
 
 fn paper_planes(right: Indeterminate, paper_planes: &mut Vec<PaperPlane>) {
     let mut stack = right.stacklayout();
-    if stack.button().text("New Paper Plane").finish() {
+    if stack
+        .button()
+        .icon(Icon::Add)
+        .text("New Paper Plane")
+        .finish()
+    {
         paper_planes.push(PaperPlane {
             paper_size: 1,
             name: "unknown".to_owned(),
@@ -124,7 +142,22 @@ fn paper_planes(right: Indeterminate, paper_planes: &mut Vec<PaperPlane>) {
             .number(&mut paper_plane.paper_size)
             .handle(&handle)
             .text("Paper Size")
+            .min(1)
+            .max(10)
             .finish()
             .unwrap();
     }
+    let mut table = stack
+        .table()
+        .editable_column("Name", CellEditor::Text)
+        .column("Paper Size");
+    for paper_plane in paper_planes.iter() {
+        table = table.row(vec![
+            paper_plane.name.clone(),
+            paper_plane.paper_size.to_string(),
+        ]);
+    }
+    for edit in table.finish() {
+        paper_planes[edit.row].name = edit.text;
+    }
 }
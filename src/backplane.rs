@@ -0,0 +1,332 @@
+use log::warn;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+use crate::{connection::Shared, Server};
+
+/// The instances of the connections are recorded again after this time, so that backplanes can
+/// let the records of instances that went away expire.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Called for every message published on a `Backplane`.
+pub type BackplaneReceiver = Box<dyn Fn(String) + Send + Sync>;
+
+/// Connects several instances of a server, e.g. behind a load balancer. Messages published by
+/// one instance reach all of them and every connection is recorded with the instance it belongs
+/// to, so that requests can be routed to it.
+pub trait Backplane: Send + Sync {
+    /// Sends `message` to all instances including this one.
+    fn publish(&self, message: String) -> io::Result<()>;
+    /// Calls `receiver` for every message published by any instance.
+    fn subscribe(&self, receiver: BackplaneReceiver) -> io::Result<()>;
+    fn set_instance(&self, uuid: Uuid, instance: &str) -> io::Result<()>;
+    fn remove_instance(&self, uuid: Uuid) -> io::Result<()>;
+    fn instance(&self, uuid: Uuid) -> io::Result<Option<String>>;
+}
+
+/// `Backplane` for several `Server`s in the same process, e.g. to try out a setup with more than
+/// one instance.
+#[derive(Default)]
+pub struct InMemoryBackplane {
+    receivers: Mutex<Vec<BackplaneReceiver>>,
+    instances: Mutex<BTreeMap<Uuid, String>>,
+}
+
+impl Backplane for InMemoryBackplane {
+    fn publish(&self, message: String) -> io::Result<()> {
+        for receiver in self.receivers.lock().iter() {
+            receiver(message.clone());
+        }
+        Ok(())
+    }
+
+    fn subscribe(&self, receiver: BackplaneReceiver) -> io::Result<()> {
+        self.receivers.lock().push(receiver);
+        Ok(())
+    }
+
+    fn set_instance(&self, uuid: Uuid, instance: &str) -> io::Result<()> {
+        self.instances.lock().insert(uuid, instance.to_owned());
+        Ok(())
+    }
+
+    fn remove_instance(&self, uuid: Uuid) -> io::Result<()> {
+        self.instances.lock().remove(&uuid);
+        Ok(())
+    }
+
+    fn instance(&self, uuid: Uuid) -> io::Result<Option<String>> {
+        Ok(self.instances.lock().get(&uuid).cloned())
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use self::redis_backplane::RedisBackplane;
+
+#[cfg(feature = "redis")]
+mod redis_backplane {
+    use log::{info, warn};
+    use parking_lot::Mutex;
+    use std::{io, thread, time::Duration};
+    use uuid::Uuid;
+
+    use super::{Backplane, BackplaneReceiver, REFRESH_INTERVAL};
+
+    const CHANNEL: &str = "iwgui:broadcast";
+
+    /// Instances are forgotten when their connection wasn't recorded again for this time, e.g.
+    /// because the server that recorded it went away. Leaves room for a few failed refreshes.
+    const INSTANCE_SECONDS: u64 = 5 * REFRESH_INTERVAL.as_secs();
+
+    /// Time between attempts to subscribe again after the subscription failed. Doubles with
+    /// every failed attempt up to `MAX_RESUBSCRIBE_DELAY`.
+    const RESUBSCRIBE_DELAY: Duration = Duration::from_millis(500);
+    const MAX_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(30);
+
+    fn io_error(err: redis::RedisError) -> io::Error {
+        io::Error::other(err)
+    }
+
+    /// New connection to the Redis server that is subscribed to `CHANNEL`.
+    fn subscribed_connection(client: &redis::Client) -> io::Result<redis::Connection> {
+        let mut connection = client.get_connection().map_err(io_error)?;
+        connection
+            .as_pubsub()
+            .subscribe(CHANNEL)
+            .map_err(io_error)?;
+        Ok(connection)
+    }
+
+    /// `Backplane` on a Redis server. Messages are sent with pub/sub and instances are stored
+    /// as keys with an expiry.
+    pub struct RedisBackplane {
+        client: redis::Client,
+        connection: Mutex<redis::Connection>,
+    }
+
+    impl RedisBackplane {
+        /// Connects to the Redis server at `url`, e.g. `redis://127.0.0.1/`.
+        pub fn connect(url: &str) -> io::Result<Self> {
+            let client = redis::Client::open(url).map_err(io_error)?;
+            let connection = client.get_connection().map_err(io_error)?;
+            Ok(Self {
+                client,
+                connection: Mutex::new(connection),
+            })
+        }
+    }
+
+    impl Backplane for RedisBackplane {
+        fn publish(&self, message: String) -> io::Result<()> {
+            redis::cmd("PUBLISH")
+                .arg(CHANNEL)
+                .arg(message)
+                .query::<()>(&mut self.connection.lock())
+                .map_err(io_error)
+        }
+
+        fn subscribe(&self, receiver: BackplaneReceiver) -> io::Result<()> {
+            let mut connection = subscribed_connection(&self.client)?;
+            let client = self.client.clone();
+            thread::spawn(move || loop {
+                let mut pubsub = connection.as_pubsub();
+                let err = loop {
+                    match pubsub.get_message().and_then(|m| m.get_payload::<String>()) {
+                        Ok(message) => receiver(message),
+                        Err(err) => break err,
+                    }
+                };
+                warn!("Stopped receiving from Redis: {}", err);
+                drop(pubsub);
+                // Broadcasts published in the meantime are lost
+                let mut delay = RESUBSCRIBE_DELAY;
+                connection = loop {
+                    thread::sleep(delay);
+                    match subscribed_connection(&client) {
+                        Ok(connection) => break connection,
+                        Err(err) => {
+                            warn!("Could not subscribe to Redis again: {}", err);
+                            delay = (delay * 2).min(MAX_RESUBSCRIBE_DELAY);
+                        }
+                    }
+                };
+                info!("Subscribed to Redis again");
+            });
+            Ok(())
+        }
+
+        fn set_instance(&self, uuid: Uuid, instance: &str) -> io::Result<()> {
+            redis::cmd("SET")
+                .arg(format!("iwgui:instance:{}", uuid))
+                .arg(instance)
+                .arg("EX")
+                .arg(INSTANCE_SECONDS)
+                .query::<()>(&mut self.connection.lock())
+                .map_err(io_error)
+        }
+
+        fn remove_instance(&self, uuid: Uuid) -> io::Result<()> {
+            redis::cmd("DEL")
+                .arg(format!("iwgui:instance:{}", uuid))
+                .query::<()>(&mut self.connection.lock())
+                .map_err(io_error)
+        }
+
+        fn instance(&self, uuid: Uuid) -> io::Result<Option<String>> {
+            redis::cmd("GET")
+                .arg(format!("iwgui:instance:{}", uuid))
+                .query(&mut self.connection.lock())
+                .map_err(io_error)
+        }
+    }
+}
+
+/// Backplane of a `Server` together with the thread that records the instances of its
+/// connections.
+pub(crate) struct Attached {
+    backplane: Arc<dyn Backplane>,
+    records: mpsc::Sender<Record>,
+}
+
+/// Change to the connections of a `Server` that the backplane is told about.
+enum Record {
+    Added(Uuid),
+    Removed(Uuid),
+}
+
+/// Records the instance of a new connection. Doesn't wait for the backplane, so it can be called
+/// while the connections are locked.
+pub(crate) fn connection_added(shared: &Shared, uuid: Uuid) {
+    if let Some(attached) = &*shared.backplane.lock() {
+        let _ = attached.records.send(Record::Added(uuid));
+    }
+}
+
+/// Forgets the instance of a connection that was removed from the `Server`.
+pub(crate) fn connection_removed(shared: &Shared, uuid: Uuid) {
+    if let Some(attached) = &*shared.backplane.lock() {
+        let _ = attached.records.send(Record::Removed(uuid));
+    }
+}
+
+/// Passes the records on to `backplane` and records the instances of all connections again
+/// every `REFRESH_INTERVAL`. Stops when the backplane is replaced or the server is dropped.
+fn record_instances(
+    shared: Weak<Shared>,
+    backplane: Arc<dyn Backplane>,
+    instance: String,
+    records: mpsc::Receiver<Record>,
+) {
+    let mut next_refresh = Instant::now() + REFRESH_INTERVAL;
+    loop {
+        let timeout = next_refresh.saturating_duration_since(Instant::now());
+        match records.recv_timeout(timeout) {
+            Ok(Record::Added(uuid)) => {
+                if let Err(err) = backplane.set_instance(uuid, &instance) {
+                    warn!("Could not record instance of {}: {}", uuid, err);
+                }
+            }
+            Ok(Record::Removed(uuid)) => {
+                if let Err(err) = backplane.remove_instance(uuid) {
+                    warn!("Could not remove instance of {}: {}", uuid, err);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let shared = match shared.upgrade() {
+                    Some(shared) => shared,
+                    None => return,
+                };
+                let uuids = shared
+                    .connections
+                    .lock()
+                    .iter()
+                    .map(|connection| connection.uuid())
+                    .collect::<Vec<_>>();
+                for uuid in uuids {
+                    if let Err(err) = backplane.set_instance(uuid, &instance) {
+                        warn!("Could not refresh instance of {}: {}", uuid, err);
+                    }
+                }
+                next_refresh = Instant::now() + REFRESH_INTERVAL;
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+impl Server {
+    /// Shares broadcasts and the instances of the connections with other servers. `instance`
+    /// names this server, e.g. its address as the load balancer sees it.
+    pub fn set_backplane(
+        &mut self,
+        backplane: Arc<dyn Backplane>,
+        instance: &str,
+    ) -> io::Result<()> {
+        let shared: Weak<Shared> = Arc::downgrade(&self.shared);
+        backplane.subscribe(Box::new(move |message| {
+            if let Some(shared) = shared.upgrade() {
+                shared.broadcasts.lock().push(message);
+                shared.notify();
+            }
+        }))?;
+        let (records, receiver) = mpsc::channel();
+        let shared = Arc::downgrade(&self.shared);
+        let recorded = backplane.clone();
+        let instance = instance.to_owned();
+        thread::spawn(move || record_instances(shared, recorded, instance, receiver));
+        // Connections made before the backplane was set
+        for connection in self.shared.connections.lock().iter() {
+            let _ = records.send(Record::Added(connection.uuid()));
+        }
+        *self.shared.backplane.lock() = Some(Attached { backplane, records });
+        Ok(())
+    }
+
+    /// Sends `message` to every instance, where it is returned by `broadcasts`. Without a
+    /// backplane it only reaches this server.
+    pub fn broadcast<T: Serialize>(&self, message: &T) -> io::Result<()> {
+        let message = serde_json::to_string(message)?;
+        match &*self.shared.backplane.lock() {
+            Some(attached) => attached.backplane.publish(message),
+            None => {
+                self.shared.broadcasts.lock().push(message);
+                self.shared.notify();
+                Ok(())
+            }
+        }
+    }
+
+    /// Messages sent with `broadcast` since the last call. They also wake up `wait_for_events`.
+    pub fn broadcasts<T: DeserializeOwned>(&mut self) -> Vec<T> {
+        let messages = std::mem::take(&mut *self.shared.broadcasts.lock());
+        messages
+            .into_iter()
+            .filter_map(|message| match serde_json::from_str::<T>(&message) {
+                Ok(message) => Some(message),
+                Err(err) => {
+                    warn!("Could not deserialize broadcast \"{}\": {}", message, err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Instance the connection with `uuid` belongs to according to the backplane.
+    pub fn instance_of(&self, uuid: Uuid) -> io::Result<Option<String>> {
+        match &*self.shared.backplane.lock() {
+            Some(attached) => attached.backplane.instance(uuid),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+use tungstenite::{client::AutoStream, error::Error, Message, WebSocket};
+use uuid::Uuid;
+
+use crate::{
+    gui::Frame,
+    protocol::{
+        BrowserLocation, BrowserServerMessage, Element, ServerBrowserMessage, WebsocketDirection,
+        DOWNLOAD_FRAME_FORMAT,
+    },
+    Event, EventKind, HandleHash, ImageFormat, ServerBrowserUpdate,
+};
+
+/// Message the `Client` received from the server.
+#[derive(Debug)]
+pub enum Received {
+    Message(ServerBrowserMessage),
+    /// Image for the live image `handle_hash`
+    Frame {
+        handle_hash: HandleHash,
+        format: ImageFormat,
+        data: Vec<u8>,
+    },
+    /// File sent with `Connection::send_file`
+    Download {
+        name: String,
+        mime: String,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    WebSocket(tungstenite::Error),
+    Json(serde_json::Error),
+    InvalidFrame,
+    Closed,
+}
+
+impl From<tungstenite::Error> for ClientError {
+    fn from(err: tungstenite::Error) -> Self {
+        ClientError::WebSocket(err)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(err: serde_json::Error) -> Self {
+        ClientError::Json(err)
+    }
+}
+
+/// Connects to a `Server` the way the page does and mirrors its GUI, for frontends that are not a
+/// browser like test drivers, terminal mirrors or bridges.
+pub struct Client {
+    uuid: Uuid,
+    to_browser: WebSocket<AutoStream>,
+    to_server: WebSocket<AutoStream>,
+    root: Option<HandleHash>,
+    layers: Vec<HandleHash>,
+    title: String,
+    elements: BTreeMap<HandleHash, Element>,
+    next_upload: u32,
+    /// Name and MIME type of downloads whose content didn't arrive yet
+    downloads: BTreeMap<u32, (String, String)>,
+    last_ping_check: Instant,
+}
+
+/// Size of the binary messages of an upload, like the page uses.
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Pings of the server are answered by `receive` at least this often.
+const PING_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+fn tcp_stream(stream: &AutoStream) -> &TcpStream {
+    #[cfg(feature = "tls")]
+    match stream {
+        tungstenite::stream::Stream::Plain(stream) => stream,
+        tungstenite::stream::Stream::Tls(stream) => stream.get_ref(),
+    }
+    #[cfg(not(feature = "tls"))]
+    stream
+}
+
+fn is_timeout(err: &Error) -> bool {
+    match err {
+        Error::Io(err) => matches!(
+            err.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ),
+        _ => false,
+    }
+}
+
+impl Client {
+    /// Connects to the websocket address of a `Server`, e.g. `ws://127.0.0.1:9001`. `wss://`
+    /// addresses need the `tls` feature.
+    pub fn connect(url: &str) -> Result<Client, ClientError> {
+        Self::connect_with_location(url, BrowserLocation::default())
+    }
+
+    /// Like `connect` but reports `location` as query and fragment of the page.
+    pub fn connect_with_location(
+        url: &str,
+        location: BrowserLocation,
+    ) -> Result<Client, ClientError> {
+        let uuid = Uuid::new_v4();
+        let (to_browser, _) = tungstenite::connect(url)?;
+        let (to_server, _) = tungstenite::connect(url)?;
+        let set_timeouts = tcp_stream(to_browser.get_ref())
+            .set_read_timeout(Some(PING_CHECK_INTERVAL))
+            .and_then(|()| {
+                tcp_stream(to_server.get_ref()).set_read_timeout(Some(Duration::from_millis(1)))
+            });
+        if let Err(err) = set_timeouts {
+            return Err(ClientError::WebSocket(Error::Io(err)));
+        }
+        let mut client = Client {
+            uuid,
+            to_browser,
+            to_server,
+            root: None,
+            layers: Vec::new(),
+            title: String::new(),
+            elements: BTreeMap::new(),
+            next_upload: 0,
+            downloads: BTreeMap::new(),
+            last_ping_check: Instant::now(),
+        };
+        // The connection exists once the websocket to the browser is registered
+        let welcome = BrowserServerMessage::Welcome {
+            direction: WebsocketDirection::ToBrowser,
+            uuid: uuid.to_string(),
+            location,
+        };
+        let welcome = serde_json::to_string(&welcome)?;
+        client.to_browser.write_message(Message::Text(welcome))?;
+        client.send(&BrowserServerMessage::Welcome {
+            direction: WebsocketDirection::ToServer,
+            uuid: uuid.to_string(),
+            location: BrowserLocation::default(),
+        })?;
+        Ok(client)
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    /// Blocks until the server sends something. Updates are applied to the mirrored GUI before
+    /// they are returned. The server pings its clients and disconnects the ones that don't
+    /// answer, which happens while waiting here, so a client has to call this regularly.
+    pub fn receive(&mut self) -> Result<Received, ClientError> {
+        loop {
+            if self.last_ping_check.elapsed() >= PING_CHECK_INTERVAL {
+                self.answer_pings()?;
+            }
+            let message = match self.to_browser.read_message() {
+                Ok(message) => message,
+                Err(err) if is_timeout(&err) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            match message {
+                Message::Text(text) => {
+                    let message = serde_json::from_str::<ServerBrowserMessage>(&text)?;
+                    match &message {
+                        ServerBrowserMessage::Update(update) => self.apply(update),
+                        ServerBrowserMessage::Download { id, name, mime } => {
+                            self.downloads.insert(*id, (name.clone(), mime.clone()));
+                        }
+                        _ => {}
+                    }
+                    return Ok(Received::Message(message));
+                }
+                Message::Binary(data) if data.get(4) == Some(&DOWNLOAD_FRAME_FORMAT) => {
+                    let id = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+                    let (name, mime) = self
+                        .downloads
+                        .remove(&id)
+                        .ok_or(ClientError::InvalidFrame)?;
+                    return Ok(Received::Download {
+                        name,
+                        mime,
+                        data: data[5..].to_vec(),
+                    });
+                }
+                Message::Binary(data) => {
+                    let (handle_hash, format, data) =
+                        Frame::from_message(&data).ok_or(ClientError::InvalidFrame)?;
+                    return Ok(Received::Frame {
+                        handle_hash,
+                        format,
+                        data: data.to_vec(),
+                    });
+                }
+                Message::Close(_) => return Err(ClientError::Closed),
+                Message::Ping(_) | Message::Pong(_) => {}
+            }
+        }
+    }
+
+    /// Reads the pings the server sent on the websocket the client sends on. Reading queues the
+    /// answers, which are written right away.
+    fn answer_pings(&mut self) -> Result<(), ClientError> {
+        self.last_ping_check = Instant::now();
+        loop {
+            match self.to_server.read_message() {
+                Ok(_) => {}
+                Err(err) if is_timeout(&err) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        match self.to_server.write_pending() {
+            Err(err) if !is_timeout(&err) => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(&mut self, update: &ServerBrowserUpdate) {
+        for handle_hash in &update.removed {
+            self.elements.remove(handle_hash);
+        }
+        for (handle_hash, element) in update.added.iter().chain(&update.updated) {
+            self.elements.insert(*handle_hash, element.clone());
+        }
+        for (handle_hash, append) in &update.appended {
+            if let Some(element) = self.elements.get_mut(handle_hash) {
+                append.apply(element);
+            }
+        }
+        if update.root.is_some() {
+            self.root = update.root;
+        }
+        if let Some(layers) = &update.layers {
+            self.layers = layers.clone();
+        }
+        if let Some(title) = &update.title {
+            self.title = title.clone();
+        }
+    }
+
+    pub fn root(&self) -> Option<HandleHash> {
+        self.root
+    }
+
+    /// Title set with `Gui::title`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Elements shown above the root, like overlays.
+    pub fn layers(&self) -> &[HandleHash] {
+        &self.layers
+    }
+
+    pub fn element(&self, handle_hash: HandleHash) -> Option<&Element> {
+        self.elements.get(&handle_hash)
+    }
+
+    pub fn elements(&self) -> &BTreeMap<HandleHash, Element> {
+        &self.elements
+    }
+
+    pub fn send_event(
+        &mut self,
+        handle_hash: HandleHash,
+        kind: EventKind,
+    ) -> Result<(), ClientError> {
+        self.send(&BrowserServerMessage::Event(Event { handle_hash, kind }))
+    }
+
+    /// Sends `bytes` as the file `name` picked in the file upload element `handle_hash`.
+    pub fn upload(
+        &mut self,
+        handle_hash: HandleHash,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<(), ClientError> {
+        let id = self.next_upload;
+        self.next_upload += 1;
+        self.send(&BrowserServerMessage::UploadStarted {
+            handle_hash,
+            id,
+            name: name.to_owned(),
+            size: bytes.len() as u64,
+        })?;
+        for chunk in bytes.chunks(UPLOAD_CHUNK_BYTES) {
+            let mut message = id.to_le_bytes().to_vec();
+            message.extend_from_slice(chunk);
+            self.to_server.write_message(Message::Binary(message))?;
+        }
+        Ok(())
+    }
+
+    /// Sends `message` like the page does, e.g. to answer a `ServerBrowserMessage::Prompt`.
+    pub fn send(&mut self, message: &BrowserServerMessage) -> Result<(), ClientError> {
+        let message = serde_json::to_string(message)?;
+        self.to_server.write_message(Message::Text(message))?;
+        Ok(())
+    }
+}
@@ -1,34 +1,240 @@
-use log::{debug, error, info, warn};
-use parking_lot::{Mutex, MutexGuard};
-use serde::Deserialize;
+#[cfg(feature = "websocket")]
+use log::error;
+use log::{debug, info, warn};
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
     collections::BTreeMap,
-    io::{Read, Write},
+    io::{self, Read, Write},
     mem,
-    net::{TcpListener, TcpStream, ToSocketAddrs},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
     slice::IterMut,
-    sync::Arc,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
     thread,
+    time::{Duration, Instant},
 };
+#[cfg(feature = "websocket")]
 use tungstenite::{error::Error, Message, WebSocket};
 use uuid::Uuid;
 
 use crate::{
-    gui::{Event, Gui},
-    EventKind, HandleHash,
+    backplane,
+    gui::Gui,
+    outbox::{Outbox, OutboxError},
+    polling::Polling,
+    protocol::{
+        BrowserLocation, BrowserServerMessage, ServerBrowserMessage, WebsocketDirection,
+        DOWNLOAD_FRAME_FORMAT,
+    },
+    session::{self, SavedSession},
+    theme::Theme,
+    Event, EventKind, Geometry, HandleHash,
 };
 
+pub use crate::protocol::{PromptHandle, SpeakOptions};
+
 pub struct Connection {
     uuid: Uuid,
-    to_browser_websocket: Option<WebSocket<TcpStream>>, // This is assigned second
+    outbox: Outbox,
     last_gui: Option<Gui>,
+    slow_client_policy: SlowClientPolicy,
+    resync: bool, // Messages were dropped and the next update has to be complete
+    deferred_gui: Option<Option<Gui>>, // Set while the GUI is built on a worker thread
+    limits: Limits,
+    session_state: Option<serde_json::Value>,
     pending_events: Arc<Mutex<BTreeMap<HandleHash, Vec<EventKind>>>>,
+    connection_events: Vec<ConnectionEvent>,
+    geometries: BTreeMap<HandleHash, Geometry>,
+    query: BTreeMap<String, String>,
+    fragment: String,
+    print_stylesheet: Option<String>,
+    tick: Option<Tick>,
+    idle_timeout: Option<Duration>,
+    idle: bool,
+    debug_overlay: bool,
+    theme: Theme,
+    next_prompt: u64,
+    next_js_request: u64,
+    js_results: BTreeMap<u64, Result<serde_json::Value, String>>,
+    taps: Vec<Sender<TapMessage>>,
+    uploads: BTreeMap<u32, Upload>,
+    next_download: u32,
+    disconnected: bool, // Removed from the `Server` on the next call to `connections`
+}
+
+/// File the browser is sending in binary chunks.
+struct Upload {
+    handle_hash: HandleHash,
+    name: String,
+    size: usize,
+    bytes: Vec<u8>,
+}
+
+/// What happens when a browser doesn't take the messages as fast as they are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SlowClientPolicy {
+    /// Drops the queued messages and sends the whole GUI with the next `Connection::show_gui`.
+    /// Other dropped messages (e.g. prompts) are lost.
+    #[default]
+    Coalesce,
+    /// Handles the browser like a closed websocket.
+    Disconnect,
+}
+
+/// Caps that keep an accidentally huge GUI from freezing the browser. Set with
+/// `Connection::set_limits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_elements: usize,
+    /// Size of the serialized update, which only contains the changes since the last one
+    pub max_update_bytes: usize,
+    pub policy: LimitPolicy,
+    /// Size of a file sent by a file upload element. Larger files are dropped.
+    pub max_upload_bytes: usize,
+    /// Files that are uploaded at the same time. Uploads started beyond it are dropped.
+    pub max_open_uploads: usize,
+    /// Sum of the sizes of the files that are uploaded at the same time
+    pub max_upload_bytes_in_flight: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_elements: 100_000,
+            max_update_bytes: 16 * 1024 * 1024,
+            policy: LimitPolicy::default(),
+            max_upload_bytes: 64 * 1024 * 1024,
+            max_open_uploads: 4,
+            max_upload_bytes_in_flight: 128 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitPolicy {
+    /// Shows the elements closest to the root and a label that tells how many were left out.
+    /// Returns `LimitExceeded` only when the root and the layers alone are too large.
+    #[default]
+    Truncate,
+    /// Doesn't show the GUI and returns `LimitExceeded` from `Connection::show_gui`.
+    Error,
+}
+
+#[derive(Debug)]
+pub enum LimitExceeded {
+    Elements { count: usize, max: usize },
+    UpdateBytes { bytes: usize, max: usize },
+}
+
+/// Events that concern the connection as a whole instead of a single element.
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    FragmentChanged(String),
+    /// Periodic event requested with `Connection::request_tick`.
+    Tick,
+    /// The user did not use pointer or keyboard for the time set with
+    /// `Connection::set_idle_timeout`.
+    IdleStart,
+    IdleEnd,
+    /// Answer to `Connection::prompt` which is `None` when the prompt was cancelled.
+    PromptAnswered {
+        prompt: PromptHandle,
+        value: Option<String>,
+    },
+    /// A function registered with `Connection::register_js_function` was called by an element.
+    JsFunctionReturned {
+        name: String,
+        result: Result<serde_json::Value, String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tick {
+    interval: Duration,
+    due: Instant,
+}
+
+/// Identifies the evaluation of JavaScript code started with `Connection::eval_js`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct JsRequest(u64);
+
+#[derive(Debug)]
+pub enum JsError {
+    Exception(String),
+    CouldNotDeserializeResult(serde_json::Error),
 }
 
 impl Connection {
+    fn new(
+        uuid: Uuid,
+        websocket: Box<dyn Transport>,
+        location: BrowserLocation,
+        theme: Theme,
+    ) -> Self {
+        Self {
+            uuid,
+            outbox: Outbox::new(websocket),
+            last_gui: None,
+            slow_client_policy: SlowClientPolicy::default(),
+            resync: false,
+            deferred_gui: None,
+            limits: Limits::default(),
+            session_state: None,
+            pending_events: Arc::new(Mutex::new(BTreeMap::new())),
+            connection_events: Vec::new(),
+            geometries: BTreeMap::new(),
+            query: location.query,
+            fragment: location.fragment,
+            print_stylesheet: None,
+            tick: None,
+            idle_timeout: None,
+            idle: false,
+            debug_overlay: false,
+            theme,
+            next_prompt: 0,
+            next_js_request: 0,
+            js_results: BTreeMap::new(),
+            taps: Vec::new(),
+            uploads: BTreeMap::new(),
+            next_download: 0,
+            disconnected: false,
+        }
+    }
+
+    /// Uses the websocket of a browser that reconnected with the same UUID. The next update
+    /// contains the whole GUI because the diff base might not have arrived.
+    fn reattach(&mut self, websocket: Box<dyn Transport>, location: BrowserLocation) {
+        self.outbox = Outbox::new(websocket);
+        self.last_gui = None;
+        self.disconnected = false;
+        self.query = location.query;
+        if self.fragment != location.fragment {
+            self.fragment = location.fragment.clone();
+            self.connection_events
+                .push(ConnectionEvent::FragmentChanged(location.fragment));
+        }
+    }
+
     pub fn gui(&mut self) -> Gui {
-        let events = self.events();
-        Gui::empty(events)
+        let mut events = self.events();
+        // Geometries are kept until they are reported again because builders ask for them every frame
+        for (handle_hash, kinds) in &mut events {
+            let geometries = &mut self.geometries;
+            kinds.retain(|kind| match kind {
+                EventKind::Measured(geometry) => {
+                    geometries.insert(*handle_hash, *geometry);
+                    false
+                }
+                _ => true,
+            });
+        }
+        events.retain(|_, kinds| !kinds.is_empty());
+        let gui = Gui::empty(events, self.geometries.clone());
+        gui.theme(self.theme.clone());
+        gui
     }
 
     fn events(&mut self) -> BTreeMap<HandleHash, Vec<EventKind>> {
@@ -36,24 +242,451 @@ impl Connection {
         mem::take(&mut *pending_events)
     }
 
-    pub fn show_gui(&mut self, gui: Gui) {
+    pub fn connection_events(&mut self) -> Vec<ConnectionEvent> {
+        mem::take(&mut self.connection_events)
+    }
+
+    /// Theme of the GUIs that are shown from now on, e.g. to switch to `Theme::dark`. Starts
+    /// with the theme of the `Server`.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Query parameters of the URL the page was initially loaded from.
+    pub fn query(&self) -> &BTreeMap<String, String> {
+        &self.query
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn query_param(&self, key: &str) -> Option<&str> {
+        self.query.get(key).map(String::as_str)
+    }
+
+    /// Current fragment of the URL without the leading `#`.
+    pub fn fragment(&self) -> &str {
+        &self.fragment
+    }
+
+    /// Sends the changes since the last call to the browser. Fails when the GUI exceeds the
+    /// `Limits` and their policy is `LimitPolicy::Error`, or when the update is still larger
+    /// than `Limits::max_update_bytes` after all elements but the root and the layers were left
+    /// out. Nothing is sent then.
+    pub fn show_gui(&mut self, gui: Gui) -> Result<(), LimitExceeded> {
         if gui.is_empty() {
+            return Ok(());
+        }
+        let Limits {
+            max_elements,
+            max_update_bytes,
+            policy,
+            ..
+        } = self.limits;
+        let count = gui.element_count();
+        if count > max_elements {
+            if policy == LimitPolicy::Error {
+                return Err(LimitExceeded::Elements {
+                    count,
+                    max: max_elements,
+                });
+            }
+            warn!("GUI of {} has {} elements, truncating", self.uuid, count);
+            gui.truncate(max_elements);
+        }
+        if let Some(deferred_gui) = &mut self.deferred_gui {
+            *deferred_gui = Some(gui);
+            return Ok(());
+        }
+        let mut message = self.update_message(&gui);
+        // Elements left after the last truncation
+        let mut truncated_to: Option<usize> = None;
+        while message.len() > max_update_bytes {
+            // With one element left only the root and the layers are shown, which can't be
+            // truncated any further
+            if policy == LimitPolicy::Error || truncated_to == Some(1) {
+                return Err(LimitExceeded::UpdateBytes {
+                    bytes: message.len(),
+                    max: max_update_bytes,
+                });
+            }
+            warn!(
+                "Update for {} has {} bytes, truncating",
+                self.uuid,
+                message.len()
+            );
+            // Assumes that all elements are about the same size. When a few large ones make the
+            // guess too high, at least half of the elements are left out in the next attempt.
+            let count = gui.element_count();
+            let max = count as u128 * max_update_bytes as u128 * 9 / 10 / message.len() as u128;
+            let max = match truncated_to {
+                Some(previous) => (max as usize).min(previous / 2),
+                None => max as usize,
+            };
+            let max = max.max(1);
+            gui.truncate(max);
+            truncated_to = Some(max);
+            message = self.update_message(&gui);
+        }
+        self.resync = false;
+        self.write(TransportMessage::Text(message));
+        for frame in gui.take_frames() {
+            self.write(TransportMessage::Binary(frame.to_message()));
+        }
+        if !self.resync {
+            self.last_gui = Some(gui);
+        }
+        Ok(())
+    }
+
+    fn update_message(&self, gui: &Gui) -> String {
+        let server_browser_update = Gui::server_browser_update(self.last_gui.as_ref(), gui);
+        serde_json::to_string(&ServerBrowserMessage::Update(server_browser_update)).unwrap()
+    }
+
+    /// Keeps `state` with the connection so that it survives a restart of the server with
+    /// `Server::save_sessions` and `Server::restore_sessions`.
+    pub fn set_session_state<T: Serialize>(&mut self, state: &T) {
+        match serde_json::to_value(state) {
+            Ok(state) => self.session_state = Some(state),
+            Err(err) => warn!(
+                "Could not serialize session state of {}: {}",
+                self.uuid, err
+            ),
+        }
+    }
+
+    pub fn session_state<T: DeserializeOwned>(&self) -> Option<T> {
+        let state = self.session_state.clone()?;
+        match serde_json::from_value(state) {
+            Ok(state) => Some(state),
+            Err(err) => {
+                warn!(
+                    "Could not deserialize session state of {}: {}",
+                    self.uuid, err
+                );
+                None
+            }
+        }
+    }
+
+    pub(crate) fn save(&self, with_gui: bool) -> SavedSession {
+        SavedSession {
+            state: self.session_state.clone(),
+            fragment: self.fragment.clone(),
+            gui: self
+                .last_gui
+                .as_ref()
+                .filter(|_| with_gui)
+                .and_then(Gui::save),
+        }
+    }
+
+    fn restore(&mut self, saved: SavedSession) {
+        self.session_state = saved.state;
+        self.last_gui = saved.gui.map(Gui::restore);
+        if self.fragment != saved.fragment {
+            self.connection_events
+                .push(ConnectionEvent::FragmentChanged(self.fragment.clone()));
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Keeps the GUI given to `show_gui` until `show_deferred_gui` so that it is diffed and
+    /// serialized after the model was unlocked.
+    pub(crate) fn defer_show_gui(&mut self) {
+        self.deferred_gui = Some(None);
+    }
+
+    pub(crate) fn show_deferred_gui(&mut self) {
+        if let Some(Some(gui)) = self.deferred_gui.take() {
+            if let Err(err) = self.show_gui(gui) {
+                warn!("GUI of {} not shown: {:?}", self.uuid, err);
+            }
+        }
+    }
+
+    /// Decides what happens when the browser is too slow to take the messages. Messages are
+    /// written on a thread per connection, so `show_gui` never waits for the browser.
+    pub fn set_slow_client_policy(&mut self, policy: SlowClientPolicy) {
+        self.slow_client_policy = policy;
+    }
+
+    /// Forgets the UI state the browser restores after a reload (scroll positions, open
+    /// collapsibles and column weights).
+    pub fn clear_ui_state(&mut self) {
+        self.send(&ServerBrowserMessage::ClearUiState);
+    }
+
+    /// Delivers `ConnectionEvent::Tick` every `interval`, which also wakes up
+    /// `Server::wait_for_events`. Requesting the same interval again keeps the running timer.
+    pub fn request_tick(&mut self, interval: Duration) {
+        match &self.tick {
+            Some(tick) if tick.interval == interval => {}
+            _ => {
+                self.tick = Some(Tick {
+                    interval,
+                    due: Instant::now() + interval,
+                })
+            }
+        }
+    }
+
+    pub fn cancel_tick(&mut self) {
+        self.tick = None;
+    }
+
+    /// Enables the detection of idle users or disables it with `None`. Only changes are sent to
+    /// the browser, so it can be set every frame.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        if self.idle_timeout != timeout {
+            self.idle_timeout = timeout;
+            let timeout_ms = timeout.map(|timeout| timeout.as_millis() as u64);
+            self.send(&ServerBrowserMessage::SetIdleTimeout(timeout_ms));
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Shows an overlay in the browser with the handle hash of the hovered element, highlights of
+    /// the elements changed by the last update and statistics about the updates. Only changes are
+    /// sent to the browser, so it can be set every frame.
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        if self.debug_overlay != enabled {
+            self.debug_overlay = enabled;
+            self.send(&ServerBrowserMessage::SetDebugOverlay(enabled));
+        }
+    }
+
+    /// Asks the user for a text in a modal dialog. The answer arrives as
+    /// `ConnectionEvent::PromptAnswered`.
+    pub fn prompt<S: Into<String>>(&mut self, text: S) -> PromptHandle {
+        self.prompt_with_default(text, String::new())
+    }
+
+    pub fn prompt_with_default<S: Into<String>, D: Into<String>>(
+        &mut self,
+        text: S,
+        default: D,
+    ) -> PromptHandle {
+        let id = self.next_prompt;
+        self.next_prompt += 1;
+        self.send(&ServerBrowserMessage::Prompt {
+            id,
+            text: text.into(),
+            default: default.into(),
+        });
+        PromptHandle(id)
+    }
+
+    /// Lets the browser download `bytes` as the file `name` with the MIME type `mime`, e.g. a
+    /// generated report as `"text/csv"`.
+    pub fn send_file<N: Into<String>, M: Into<String>>(&mut self, name: N, bytes: &[u8], mime: M) {
+        let id = self.next_download;
+        self.next_download += 1;
+        self.send(&ServerBrowserMessage::Download {
+            id,
+            name: name.into(),
+            mime: mime.into(),
+        });
+        let mut message = Vec::with_capacity(5 + bytes.len());
+        message.extend_from_slice(&id.to_le_bytes());
+        message.push(DOWNLOAD_FRAME_FORMAT);
+        message.extend_from_slice(bytes);
+        self.write(TransportMessage::Binary(message));
+    }
+
+    /// Opens the print dialog of the browser.
+    pub fn print(&mut self) {
+        self.send(&ServerBrowserMessage::Print);
+    }
+
+    /// Sets CSS that is only applied when the page is printed. The stylesheet is only sent
+    /// when it differs from the last one, so it can be set every frame.
+    pub fn set_print_stylesheet<S: Into<String>>(&mut self, css: S) {
+        let css = css.into();
+        if self.print_stylesheet.as_ref() != Some(&css) {
+            self.send(&ServerBrowserMessage::PrintStylesheet(css.clone()));
+            self.print_stylesheet = Some(css);
+        }
+    }
+
+    /// Evaluates `code` as the body of a function in the browser. The returned value (awaited
+    /// if it is a promise) can be retrieved with `poll_js` once the browser answered.
+    pub fn eval_js<S: Into<String>>(&mut self, code: S) -> JsRequest {
+        let id = self.next_js_request;
+        self.next_js_request += 1;
+        self.send(&ServerBrowserMessage::EvalJs {
+            id,
+            code: code.into(),
+        });
+        JsRequest(id)
+    }
+
+    pub fn poll_js<T: DeserializeOwned>(
+        &mut self,
+        request: JsRequest,
+    ) -> Option<Result<T, JsError>> {
+        let result = self.js_results.remove(&request.0)?;
+        Some(match result {
+            Ok(value) => serde_json::from_value(value).map_err(JsError::CouldNotDeserializeResult),
+            Err(exception) => Err(JsError::Exception(exception)),
+        })
+    }
+
+    /// Defines a JavaScript function in the browser which elements can call by `name` (see
+    /// `ButtonBuilder::call_js`). `code` is the body of the function and gets the handle hash
+    /// of the calling element as `handle_hash`.
+    pub fn register_js_function<N: Into<String>, C: Into<String>>(&mut self, name: N, code: C) {
+        self.send(&ServerBrowserMessage::RegisterJsFunction {
+            name: name.into(),
+            code: code.into(),
+        });
+    }
+
+    /// Reads `text` out loud in the browser. Falls back to an ARIA live region when the
+    /// browser has no speech synthesis.
+    pub fn speak<S: Into<String>>(&mut self, text: S, options: SpeakOptions) {
+        self.send(&ServerBrowserMessage::Speak {
+            text: text.into(),
+            options,
+        });
+    }
+
+    pub(crate) fn send(&mut self, message: &ServerBrowserMessage) {
+        let message = serde_json::to_string(message).unwrap();
+        self.write(TransportMessage::Text(message));
+    }
+
+    /// Mirrors all messages exchanged with the browser from now on, e.g. to debug diffing and event
+    /// routing. Stops when the `Receiver` is dropped.
+    pub fn tap(&mut self) -> Receiver<TapMessage> {
+        let (sender, receiver) = mpsc::channel();
+        self.taps.push(sender);
+        receiver
+    }
+
+    fn send_to_taps(&mut self, direction: WebsocketDirection, message: &str) {
+        self.taps.retain(|tap| {
+            tap.send(TapMessage {
+                direction,
+                message: message.to_owned(),
+            })
+            .is_ok()
+        });
+    }
+
+    fn write(&mut self, message: TransportMessage) {
+        if !self.taps.is_empty() {
+            match &message {
+                TransportMessage::Text(text) => {
+                    self.send_to_taps(WebsocketDirection::ToBrowser, text)
+                }
+                TransportMessage::Binary(data) => self.send_to_taps(
+                    WebsocketDirection::ToBrowser,
+                    &format!("<binary message of {} bytes>", data.len()),
+                ),
+            }
+        }
+        if self.disconnected {
+            // A browser that reconnects with the same UUID needs everything it missed
+            self.resync();
             return;
         }
-        let server_browser_update = Gui::server_browser_update(self.last_gui.as_ref(), &gui);
-        if let Some(to_browser_websocket) = &mut self.to_browser_websocket {
-            let message = serde_json::to_string(&server_browser_update).unwrap();
-            match to_browser_websocket.write_message(Message::Text(message)) {
-                Ok(()) => {}
-                Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::ConnectionAborted => {
-                    // Happens when the page is reloaded
+        match self.outbox.push(message) {
+            Ok(()) => {}
+            Err(OutboxError::Full) => match self.slow_client_policy {
+                SlowClientPolicy::Coalesce => {
+                    info!(
+                        "Connection {} is too slow, dropping queued messages",
+                        self.uuid
+                    );
+                    self.outbox.clear();
+                    self.resync();
+                }
+                SlowClientPolicy::Disconnect => {
+                    warn!("Connection {} is too slow, disconnecting", self.uuid);
+                    self.disconnected = true;
                 }
-                Err(err) => panic!("{}", err),
+            },
+            Err(OutboxError::Transport(err)) if err.kind() == io::ErrorKind::ConnectionAborted => {
+                // Happens when the page is reloaded
+                info!("Connection {} aborted", self.uuid);
+                self.disconnected = true;
+                self.resync();
+            }
+            Err(OutboxError::Transport(err)) => {
+                warn!("Could not send to {}: {}", self.uuid, err);
+                self.disconnected = true;
+                self.resync();
             }
-        } else {
-            warn!("Gui ready for sending but no 'to_browser_websocket' found");
         }
-        self.last_gui = Some(gui);
+    }
+
+    /// Turns the upload into an event once all of its bytes arrived.
+    fn complete_upload(&mut self, id: u32) -> bool {
+        match self.uploads.get(&id) {
+            Some(upload) if upload.bytes.len() == upload.size => {}
+            _ => return false,
+        }
+        let upload = self.uploads.remove(&id).expect("checked above");
+        info!("Received \"{}\" with {} bytes", upload.name, upload.size);
+        self.pending_events
+            .lock()
+            .entry(upload.handle_hash)
+            .or_default()
+            .push(EventKind::FileUploaded {
+                name: upload.name,
+                bytes: upload.bytes,
+            });
+        true
+    }
+
+    /// Forgets what the browser has so that the next `show_gui` sends the whole GUI. Also covers
+    /// an update that is sent in the moment because the failed message might be part of it.
+    fn resync(&mut self) {
+        self.last_gui = None;
+        self.resync = true;
+    }
+}
+
+/// Message exchanged with the browser as mirrored by `Connection::tap`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TapMessage {
+    pub direction: WebsocketDirection,
+    pub message: String,
+}
+
+/// Message for the browser as it is handed to the `Transport`.
+pub enum TransportMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Channel to the browser of a `Connection`. The websockets managed by the `Server` implement it
+/// and other websocket implementations are plugged in with `Server::socket`.
+pub trait Transport: Send {
+    fn send(&mut self, message: TransportMessage) -> io::Result<()>;
+}
+
+#[cfg(feature = "websocket")]
+impl Transport for WebSocket<TcpStream> {
+    fn send(&mut self, message: TransportMessage) -> io::Result<()> {
+        let message = match message {
+            TransportMessage::Text(text) => Message::Text(text),
+            TransportMessage::Binary(data) => Message::Binary(data),
+        };
+        match self.write_message(message) {
+            Ok(()) => Ok(()),
+            Err(Error::Io(err)) => Err(err),
+            Err(err) => Err(io::Error::other(err.to_string())),
+        }
     }
 }
 
@@ -69,44 +702,365 @@ impl<'a, 'b: 'a> IntoIterator for &'a mut Connections<'b> {
     }
 }
 
-const WEBSOCKET_ADDRESS: &'static str = "127.0.0.1:9001";
+#[cfg(feature = "websocket")]
+const WEBSOCKET_PORT: u16 = 9001;
+
+/// The browser is pinged when nothing arrived on its websocket for this time.
+#[cfg(feature = "websocket")]
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Time the browser has to answer a ping before its connection counts as disconnected.
+#[cfg(feature = "websocket")]
+const PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Writes to the browser that take longer than this fail, e.g. because the TCP connection is
+/// half-open and the send buffer is full.
+#[cfg(feature = "websocket")]
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Size of the request line and headers of an HTTP request
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+/// Size of the body of an HTTP request, which holds at most one message from the browser
+const MAX_REQUEST_BODY_BYTES: usize = 1024 * 1024;
+
+/// Browsers that take longer than this to send an HTTP request are dropped.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// State shared between the `Server` and the threads handling the websockets.
+pub(crate) struct Shared {
+    pub(crate) connections: Mutex<Vec<Connection>>,
+    events_pending: Mutex<bool>,
+    events_arrived: Condvar,
+    pub(crate) polling: Polling,
+    pub(crate) backplane: Mutex<Option<backplane::Attached>>,
+    pub(crate) broadcasts: Mutex<Vec<String>>,
+    pub(crate) restored: Mutex<BTreeMap<Uuid, SavedSession>>,
+    /// MIME type and content of the icon of the page
+    favicon: Mutex<Option<(String, Vec<u8>)>>,
+    /// Theme of new connections
+    theme: Mutex<Theme>,
+    /// Page that is served instead of `web/index.html`
+    index_html: Option<String>,
+    #[cfg(feature = "chartjs")]
+    chart_js: Option<String>,
+    /// Token that gives access to the inspector from other hosts than the local one
+    #[cfg(feature = "inspector")]
+    pub(crate) inspector_token: Option<String>,
+}
+
+impl Shared {
+    /// Wakes up `Server::wait_for_events`.
+    pub(crate) fn notify(&self) {
+        *self.events_pending.lock() = true;
+        self.events_arrived.notify_all();
+    }
+}
+
+/// Websocket that is not managed by the `Server`. Created by `Server::socket`. Dropping it
+/// disconnects the connection when the browser uses it to send to the server.
+pub struct Socket {
+    shared: Arc<Shared>,
+    transport: Option<Box<dyn Transport>>,
+    uuid: Option<Uuid>, // Set when the browser sends events on this websocket
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if let Some(uuid) = self.uuid {
+            disconnect(&self.shared, uuid);
+        }
+    }
+}
+
+impl Socket {
+    pub(crate) fn new(shared: Arc<Shared>, transport: Box<dyn Transport>) -> Self {
+        Self {
+            shared,
+            transport: Some(transport),
+            uuid: None,
+        }
+    }
+
+    pub fn receive(&mut self, message: &str) {
+        if let Some(uuid) = self.uuid {
+            handle_incoming_event(message, &self.shared, uuid);
+            return;
+        }
+        match serde_json::from_str::<BrowserServerMessage>(message) {
+            Ok(BrowserServerMessage::Welcome {
+                direction,
+                uuid,
+                location,
+            }) => match Uuid::parse_str(&uuid) {
+                Ok(uuid) => match direction {
+                    WebsocketDirection::ToBrowser => match self.transport.take() {
+                        Some(transport) => add_connection(&self.shared, uuid, transport, location),
+                        None => warn!("Second welcome message on websocket of {}", uuid),
+                    },
+                    WebsocketDirection::ToServer => self.uuid = Some(uuid),
+                },
+                Err(err) => {
+                    warn!("Could not parse uuid in 'welcome' message: {}", err);
+                    self.reject(format!("Invalid uuid \"{}\"", uuid));
+                }
+            },
+            Ok(_) => {
+                warn!("Expected 'welcome' message: {}", message);
+                self.reject("Expected 'welcome' message".to_owned());
+            }
+            Err(err) => warn!("Could not deserialize \"{}\": {}", message, err),
+        }
+    }
+
+    /// Handles a binary message, e.g. a chunk of a file upload. Only the browser sends them
+    /// after the `Welcome`.
+    pub fn receive_binary(&mut self, data: &[u8]) {
+        match self.uuid {
+            Some(uuid) => handle_incoming_binary(data, &self.shared, uuid),
+            None => warn!("Binary message before 'welcome' message"),
+        }
+    }
+
+    fn reject(&mut self, error: String) {
+        if let Some(transport) = &mut self.transport {
+            reject(transport.as_mut(), error);
+        }
+    }
+}
 
 pub struct Server {
-    connections: Arc<Mutex<Vec<Connection>>>,
+    pub(crate) shared: Arc<Shared>,
+    address: SocketAddr,
+    #[cfg(feature = "mdns")]
+    pub(crate) mdns: Option<mdns_sd::ServiceDaemon>,
+    disconnected: Vec<Uuid>,
+}
+
+/// Configures a `Server` before it starts listening.
+#[derive(Debug, Default)]
+pub struct ServerBuilder {
+    index_html: Option<String>,
+    #[cfg(feature = "chartjs")]
+    chart_js: Option<String>,
+    #[cfg(feature = "inspector")]
+    inspector_token: Option<String>,
+}
+
+impl ServerBuilder {
+    /// Page that is served instead of the built-in one, e.g. to add meta tags, fonts or a
+    /// header around the GUI. The styles, the script and the elements the GUI is mounted in are
+    /// put in place of `<!-- iwgui -->`, which should be in the `<body>`.
+    pub fn index_html<S: Into<String>>(mut self, template: S) -> Self {
+        self.index_html = Some(template.into());
+        self
+    }
+
+    /// Source of Chart.js (`chart.umd.min.js`, version 4) that the page loads instead of the
+    /// copy on the jsDelivr CDN, so charts also work without access to the internet.
+    #[cfg(feature = "chartjs")]
+    pub fn chart_js<S: Into<String>>(mut self, source: S) -> Self {
+        self.chart_js = Some(source.into());
+        self
+    }
+
+    /// Token that has to be passed as `?token=` to open the inspector at `/_protocol`. Without
+    /// it the inspector is only served to the local host, because it reveals the uuids of the
+    /// connections, which are enough to take them over.
+    #[cfg(feature = "inspector")]
+    pub fn inspector_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.inspector_token = Some(token.into());
+        self
+    }
+
+    pub fn build<A: ToSocketAddrs + Send + 'static>(self, address: A) -> Server {
+        Server::with_builder(self, address)
+    }
 }
 
 impl Server {
     pub fn new<A: ToSocketAddrs + Send + 'static>(address: A) -> Self {
-        let connections = Arc::new(Mutex::new(Vec::new()));
+        Self::builder().build(address)
+    }
+
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    fn with_builder<A: ToSocketAddrs + Send + 'static>(builder: ServerBuilder, address: A) -> Self {
+        let shared = Arc::new(Shared {
+            connections: Mutex::new(Vec::new()),
+            events_pending: Mutex::new(false),
+            events_arrived: Condvar::new(),
+            polling: Polling::default(),
+            backplane: Mutex::new(None),
+            broadcasts: Mutex::new(Vec::new()),
+            restored: Mutex::new(BTreeMap::new()),
+            favicon: Mutex::new(None),
+            theme: Mutex::new(Theme::default()),
+            index_html: builder.index_html,
+            #[cfg(feature = "chartjs")]
+            chart_js: builder.chart_js,
+            #[cfg(feature = "inspector")]
+            inspector_token: builder.inspector_token,
+        });
+        let listener = TcpListener::bind(address).unwrap();
+        let address = listener.local_addr().unwrap();
+        let http_shared = shared.clone();
         thread::spawn(move || {
-            let listener = TcpListener::bind(address).unwrap();
             for stream in listener.incoming() {
                 match stream {
-                    Ok(stream) => handle_incoming_connection(stream),
+                    Ok(stream) => handle_incoming_connection(stream, http_shared.clone()),
                     Err(err) => {
                         panic!("Could not retrieve incoming stream of connection: {}", err);
                     }
                 }
             }
         });
-        spawn_incoming_thread(WEBSOCKET_ADDRESS, connections.clone());
-        Self { connections }
+        #[cfg(feature = "hot-reload")]
+        crate::hot_reload::watch(shared.clone());
+        #[cfg(feature = "websocket")]
+        spawn_incoming_thread(
+            SocketAddr::new(address.ip(), WEBSOCKET_PORT),
+            shared.clone(),
+        );
+        Self {
+            shared,
+            address,
+            #[cfg(feature = "mdns")]
+            mdns: None,
+            disconnected: Vec::new(),
+        }
     }
 
-    pub fn connections<'a>(&mut self) -> Connections {
-        let connections = self.connections.lock();
+    /// Creates the receiving end for a websocket of another implementation. Every text message the
+    /// browser sends on it has to be passed to `Socket::receive`. `transport` sends on the same
+    /// websocket and is used when the browser picks it for the messages to the browser.
+    pub fn socket<T: Transport + 'static>(&self, transport: T) -> Socket {
+        Socket::new(self.shared.clone(), Box::new(transport))
+    }
+
+    /// Icon the browser shows in the tab, e.g. `set_favicon(bytes, "image/png")`.
+    pub fn set_favicon<B: Into<Vec<u8>>>(&self, bytes: B, mime: &str) {
+        *self.shared.favicon.lock() = Some((mime.to_owned(), bytes.into()));
+    }
+
+    /// Theme of the connections that are made from now on. Each connection can switch with
+    /// `Connection::set_theme`.
+    pub fn set_theme(&self, theme: Theme) {
+        *self.shared.theme.lock() = theme;
+    }
+
+    /// Address the page is served on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn connections(&mut self) -> Connections<'_> {
+        let mut connections = self.shared.connections.lock();
+        remove_disconnected(&self.shared, &mut connections, &mut self.disconnected);
+        fire_ticks(&mut connections);
         Connections { r: connections }
     }
+
+    /// Connections that were closed by the browser or failed since the last call. They are
+    /// removed from `connections` together with their pending events.
+    pub fn disconnected(&mut self) -> Vec<Uuid> {
+        let mut connections = self.shared.connections.lock();
+        remove_disconnected(&self.shared, &mut connections, &mut self.disconnected);
+        mem::take(&mut self.disconnected)
+    }
+
+    /// Blocks until the browser sent something, a tick requested with
+    /// `Connection::request_tick` is due or `timeout` elapsed. This allows for a main loop that
+    /// only rebuilds the GUI when something happened.
+    pub fn wait_for_events(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let next_tick = {
+                let mut connections = self.shared.connections.lock();
+                if fire_ticks(&mut connections) {
+                    return;
+                }
+                connections
+                    .iter()
+                    .filter_map(|c| c.tick)
+                    .map(|t| t.due)
+                    .min()
+            };
+            let mut events_pending = self.shared.events_pending.lock();
+            if !*events_pending {
+                let wake_up = next_tick.map_or(deadline, |due| due.min(deadline));
+                self.shared
+                    .events_arrived
+                    .wait_until(&mut events_pending, wake_up);
+            }
+            if *events_pending {
+                *events_pending = false;
+                return;
+            }
+            if Instant::now() >= deadline {
+                return;
+            }
+        }
+    }
+}
+
+fn remove_disconnected(
+    shared: &Shared,
+    connections: &mut Vec<Connection>,
+    disconnected: &mut Vec<Uuid>,
+) {
+    connections.retain(|connection| {
+        if connection.disconnected {
+            info!("Removing connection {}", connection.uuid);
+            backplane::connection_removed(shared, connection.uuid);
+            disconnected.push(connection.uuid);
+        }
+        !connection.disconnected
+    });
 }
 
-fn spawn_incoming_thread(address: &'static str, connections: Arc<Mutex<Vec<Connection>>>) {
+/// Marks the connection as disconnected after the websocket to the server was closed.
+fn disconnect(shared: &Shared, uuid: Uuid) {
+    if let Some(connection) = shared
+        .connections
+        .lock()
+        .iter_mut()
+        .find(|connection| connection.uuid == uuid)
+    {
+        connection.disconnected = true;
+    }
+    shared.notify();
+}
+
+/// Delivers `ConnectionEvent::Tick` to all connections whose tick is due. Returns whether a tick
+/// was fired.
+fn fire_ticks(connections: &mut [Connection]) -> bool {
+    let now = Instant::now();
+    let mut fired = false;
+    for connection in connections {
+        if let Some(tick) = &mut connection.tick {
+            if tick.due <= now {
+                // Ticks that were missed are not made up for
+                tick.due = now + tick.interval;
+                connection.connection_events.push(ConnectionEvent::Tick);
+                fired = true;
+            }
+        }
+    }
+    fired
+}
+
+#[cfg(feature = "websocket")]
+fn spawn_incoming_thread(address: SocketAddr, shared: Arc<Shared>) {
     thread::spawn(move || {
         let server = TcpListener::bind(address).unwrap();
         for stream in server.incoming() {
             info!("Incoming websocket connection");
             match stream {
                 Ok(stream) => {
-                    handle_incoming_websocket_connection(stream, connections.clone());
+                    handle_incoming_websocket_connection(stream, shared.clone());
                 }
                 Err(err) => {
                     error!("{}", err);
@@ -116,127 +1070,307 @@ fn spawn_incoming_thread(address: &'static str, connections: Arc<Mutex<Vec<Conne
     });
 }
 
-#[derive(Clone, Copy, Deserialize)]
-enum WebsocketDirection {
-    ToBrowser,
-    ToServer,
-}
-
-#[derive(Deserialize)]
-enum BrowserServerMessage {
-    Welcome {
-        direction: WebsocketDirection,
-        uuid: String,
-    },
-    Event(Event),
+/// Handles a chunk of an upload: the id of the upload (u32, little endian) followed by the data.
+fn handle_incoming_binary(data: &[u8], shared: &Shared, uuid: Uuid) {
+    let (id, chunk) = match data {
+        [a, b, c, d, chunk @ ..] => (u32::from_le_bytes([*a, *b, *c, *d]), chunk),
+        _ => {
+            warn!("Binary message from {} is too short", uuid);
+            return;
+        }
+    };
+    let mut connections = shared.connections.lock();
+    let Some(connection) = connections.iter_mut().find(|c| c.uuid == uuid) else {
+        warn!(
+            "Binary message from browser but no connection found for {}",
+            uuid
+        );
+        return;
+    };
+    connection.send_to_taps(
+        WebsocketDirection::ToServer,
+        &format!("<chunk of {} bytes of upload {}>", chunk.len(), id),
+    );
+    let Some(upload) = connection.uploads.get_mut(&id) else {
+        // Happens for the rest of an upload that was too large
+        return;
+    };
+    if upload.bytes.len() + chunk.len() > upload.size {
+        warn!(
+            "Upload \"{}\" from {} is larger than announced",
+            upload.name, uuid
+        );
+        connection.uploads.remove(&id);
+        return;
+    }
+    upload.bytes.extend_from_slice(chunk);
+    if connection.complete_upload(id) {
+        shared.notify();
+    }
 }
 
-fn handle_incoming_event(message: &str, connections: Arc<Mutex<Vec<Connection>>>, uuid: Uuid) {
-    let pending_events = {
-        let connections = connections.lock();
-        let connection = connections.iter().find(|c| c.uuid == uuid);
-        if let Some(connection) = connection {
-            connection.pending_events.clone()
-        } else {
+fn handle_incoming_event(text: &str, shared: &Shared, uuid: Uuid) {
+    let message = match serde_json::from_str::<BrowserServerMessage>(text) {
+        Ok(message) => message,
+        Err(err) => {
+            warn!("Could not deserialize event \"{}\": {}", text, err);
+            return;
+        }
+    };
+    let mut connections = shared.connections.lock();
+    let connection = match connections.iter_mut().find(|c| c.uuid == uuid) {
+        Some(connection) => connection,
+        None => {
             warn!("Event from browser but to connection found for {}", uuid);
             return;
         }
     };
-    match serde_json::from_str::<BrowserServerMessage>(message) {
-        Ok(BrowserServerMessage::Event(event)) => {
+    connection.send_to_taps(WebsocketDirection::ToServer, text);
+    shared.notify();
+    match message {
+        BrowserServerMessage::Event(Event {
+            kind: EventKind::FileUploaded { name, .. },
+            ..
+        }) => {
+            // Uploads have to go through the binary messages to be checked against the limits
+            warn!(
+                "Ignoring uploaded file \"{}\" sent as event by {}",
+                name, uuid
+            );
+        }
+        BrowserServerMessage::Event(event) => {
             info!("Received event: {:?}", event);
-            let mut pending_events = pending_events.lock();
-            pending_events
-                .entry(event.handle_hash)
-                .and_modify(|vec| vec.push(event.kind.clone()))
-                .or_insert(vec![event.kind]);
+            let mut pending_events = connection.pending_events.lock();
+            let kinds = pending_events.entry(event.handle_hash).or_default();
+            // Live values arrive faster than frames are built, only the latest one is kept
+            match kinds.last_mut() {
+                Some(last)
+                    if event.kind.replaces_earlier()
+                        && mem::discriminant(last) == mem::discriminant(&event.kind) =>
+                {
+                    *last = event.kind
+                }
+                _ => kinds.push(event.kind),
+            }
         }
-        Ok(BrowserServerMessage::Welcome { .. }) => {
-            todo!()
+        BrowserServerMessage::UploadStarted {
+            handle_hash,
+            id,
+            name,
+            size,
+        } => {
+            let size = size as usize;
+            if connection.uploads.remove(&id).is_some() {
+                // The chunks can't be told apart, so neither upload is kept
+                warn!("Upload {} from {} was started twice", id, uuid);
+                return;
+            }
+            let limits = connection.limits;
+            let in_flight = connection
+                .uploads
+                .values()
+                .map(|upload| upload.size)
+                .sum::<usize>();
+            if size > limits.max_upload_bytes
+                || connection.uploads.len() >= limits.max_open_uploads
+                || in_flight + size > limits.max_upload_bytes_in_flight
+            {
+                warn!(
+                    "Dropping upload of \"{}\" from {} with {} bytes ({} other uploads with {} bytes)",
+                    name,
+                    uuid,
+                    size,
+                    connection.uploads.len(),
+                    in_flight
+                );
+                return;
+            }
+            // The buffer grows with the chunks instead of trusting the announced size
+            connection.uploads.insert(
+                id,
+                Upload {
+                    handle_hash,
+                    name,
+                    size,
+                    bytes: Vec::new(),
+                },
+            );
+            // Empty files don't get any chunks
+            connection.complete_upload(id);
         }
-        Err(err) => {
-            warn!("Could not deserialize event \"{}\": {}", message, err);
+        BrowserServerMessage::FragmentChanged(fragment) => {
+            info!("Fragment changed to \"{}\" on {}", fragment, uuid);
+            connection.fragment = fragment.clone();
+            connection
+                .connection_events
+                .push(ConnectionEvent::FragmentChanged(fragment));
+        }
+        BrowserServerMessage::JsResult { id, result } => {
+            connection.js_results.insert(id, result);
+        }
+        BrowserServerMessage::JsFunctionReturned { name, result } => {
+            connection
+                .connection_events
+                .push(ConnectionEvent::JsFunctionReturned { name, result });
+        }
+        BrowserServerMessage::IdleChanged(idle) => {
+            connection.idle = idle;
+            connection.connection_events.push(if idle {
+                ConnectionEvent::IdleStart
+            } else {
+                ConnectionEvent::IdleEnd
+            });
+        }
+        BrowserServerMessage::PromptAnswered { id, value } => {
+            connection
+                .connection_events
+                .push(ConnectionEvent::PromptAnswered { prompt: id, value });
         }
+        BrowserServerMessage::Welcome { .. } => {
+            warn!("Second welcome message from {}", uuid);
+            connection.send(&ServerBrowserMessage::ProtocolError(
+                "Websocket already welcomed".to_owned(),
+            ));
+        }
+    }
+}
+
+/// Tells the browser why its message on `transport` is ignored.
+fn reject(transport: &mut dyn Transport, error: String) {
+    let message = serde_json::to_string(&ServerBrowserMessage::ProtocolError(error)).unwrap();
+    if let Err(err) = transport.send(TransportMessage::Text(message)) {
+        warn!("Could not send protocol error: {}", err);
+    }
+}
+
+fn add_connection(
+    shared: &Shared,
+    uuid: Uuid,
+    transport: Box<dyn Transport>,
+    location: BrowserLocation,
+) {
+    let mut connections = shared.connections.lock();
+    if let Some(connection) = connections.iter_mut().find(|c| c.uuid == uuid) {
+        info!("Reconnected {}", uuid);
+        connection.reattach(transport, location);
+        shared.notify();
+        return;
+    }
+    let theme = shared.theme.lock().clone();
+    let mut connection = Connection::new(uuid, transport, location, theme);
+    if let Some(saved) = session::take_restored(shared, uuid) {
+        info!("Restoring session of {}", uuid);
+        connection.restore(saved);
     }
+    connections.push(connection);
+    backplane::connection_added(shared, uuid);
+    shared.notify();
+    let connections_array = connections
+        .iter()
+        .map(|c| c.uuid.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    debug!("Connections: [{}]", connections_array);
 }
 
+#[cfg(feature = "websocket")]
 fn handle_welcome_message(
     websocket: WebSocket<TcpStream>,
-    connections: Arc<Mutex<Vec<Connection>>>,
+    shared: Arc<Shared>,
     direction: WebsocketDirection,
     uuid: &str,
+    location: BrowserLocation,
 ) {
     info!("Received welcome message from {}", uuid);
     if let Ok(uuid) = Uuid::parse_str(uuid) {
         match direction {
             WebsocketDirection::ToBrowser => {
-                let connection = Connection {
-                    to_browser_websocket: Some(websocket),
-                    uuid,
-                    last_gui: None,
-                    pending_events: Arc::new(Mutex::new(BTreeMap::new())),
-                };
-                let mut connections = connections.lock();
-                connections.push(connection);
-                let connections_array = connections
-                    .iter()
-                    .map(|c| c.uuid.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ");
-                debug!("Connections: {}", format!("[{}]", connections_array));
+                if let Err(err) = websocket.get_ref().set_write_timeout(Some(WRITE_TIMEOUT)) {
+                    warn!("Could not set the write timeout of {}: {}", uuid, err);
+                }
+                add_connection(&shared, uuid, Box::new(websocket), location);
             }
             WebsocketDirection::ToServer => {
                 let mut websocket = websocket;
+                // The read times out to ping the browser, which detects half-open connections
+                if let Err(err) = websocket.get_ref().set_read_timeout(Some(PING_INTERVAL)) {
+                    warn!("Could not set the read timeout of {}: {}", uuid, err);
+                }
+                let mut last_received = Instant::now();
                 loop {
-                    match websocket.read_message() {
+                    let message = websocket.read_message();
+                    if message.is_ok() {
+                        last_received = Instant::now();
+                    }
+                    match message {
                         Ok(Message::Text(message)) => {
-                            handle_incoming_event(&message, connections.clone(), uuid)
+                            handle_incoming_event(&message, &shared, uuid)
+                        }
+                        Ok(Message::Binary(data)) => handle_incoming_binary(&data, &shared, uuid),
+                        Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {}
+                        Err(Error::Io(err))
+                            if matches!(
+                                err.kind(),
+                                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                            ) =>
+                        {
+                            if last_received.elapsed() >= PING_INTERVAL + PONG_TIMEOUT {
+                                info!("No answer to ping from {}", uuid);
+                                break;
+                            }
+                            if let Err(err) = websocket.write_message(Message::Ping(Vec::new())) {
+                                warn!("Could not ping {}: {}", uuid, err);
+                                break;
+                            }
                         }
                         Ok(Message::Close(_)) => {
                             info!("Closing websocket {}", uuid);
                             break;
                         }
-                        Ok(unexpected_message) => {
-                            warn!("Unexpected message: {:?}", unexpected_message)
-                        }
                         Err(Error::ConnectionClosed) => {
                             info!("Connection closed {}", uuid);
                             break;
                         }
                         Err(err) => {
-                            panic!("Panic {:?}", err);
+                            warn!("Could not read from {}: {}", uuid, err);
+                            break;
                         }
                     }
                 }
+                disconnect(&shared, uuid);
             }
         }
     } else {
-        panic!(
-            "Could not parse uuid message in 'welcome' message: {}",
-            uuid
-        );
+        warn!("Could not parse uuid in 'welcome' message: {}", uuid);
+        let mut websocket = websocket;
+        reject(&mut websocket, format!("Invalid uuid \"{}\"", uuid));
     }
 }
 
-fn handle_incoming_websocket_connection(
-    stream: TcpStream,
-    connections: Arc<Mutex<Vec<Connection>>>,
-) {
+#[cfg(feature = "websocket")]
+fn handle_incoming_websocket_connection(stream: TcpStream, shared: Arc<Shared>) {
     thread::spawn(move || {
         info!("Started websocket connection thread");
         match tungstenite::server::accept(stream) {
             Ok(mut websocket) => match websocket.read_message() {
                 Ok(Message::Text(text)) => {
                     match serde_json::from_str::<BrowserServerMessage>(&text) {
-                        Ok(BrowserServerMessage::Welcome { direction, uuid }) => {
-                            handle_welcome_message(websocket, connections, direction, &uuid);
+                        Ok(BrowserServerMessage::Welcome {
+                            direction,
+                            uuid,
+                            location,
+                        }) => {
+                            handle_welcome_message(websocket, shared, direction, &uuid, location);
+                        }
+                        Ok(_) => {
+                            warn!("Expected 'welcome' message: {}", text);
+                            reject(&mut websocket, "Expected 'welcome' message".to_owned());
                         }
-                        Ok(_other) => todo!(),
-                        Err(err) => panic!("{}", err),
+                        Err(err) => warn!("Could not deserialize \"{}\": {}", text, err),
                     }
                 }
                 Ok(..) => warn!("Unknown message type from websocket"),
-                Err(err) => panic!("{}", err),
+                Err(err) => warn!("Could not read welcome message: {}", err),
             },
             Err(err) => {
                 error!("{}", err);
@@ -245,7 +1379,118 @@ fn handle_incoming_websocket_connection(
     });
 }
 
-fn handle_incoming_connection(mut stream: TcpStream) {
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: BTreeMap<String, String>,
+    body: String,
+}
+
+enum HttpRequestError {
+    Io(io::Error),
+    /// The head or body is larger than `MAX_REQUEST_HEAD_BYTES` or `MAX_REQUEST_BODY_BYTES`.
+    TooLarge,
+}
+
+impl From<io::Error> for HttpRequestError {
+    fn from(err: io::Error) -> Self {
+        HttpRequestError::Io(err)
+    }
+}
+
+fn read_http_request(stream: &mut TcpStream) -> Result<HttpRequest, HttpRequestError> {
+    stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT))?;
+    let mut data = Vec::new();
+    let mut buffer = [0; 1024];
+    let header_end = loop {
+        if let Some(position) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break position + 4;
+        }
+        if data.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(HttpRequestError::TooLarge);
+        }
+        match stream.read(&mut buffer)? {
+            0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            bytes_read => data.extend_from_slice(&buffer[..bytes_read]),
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_owned();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_owned();
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect();
+    let content_length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_BODY_BYTES {
+        return Err(HttpRequestError::TooLarge);
+    }
+    let request_end = header_end
+        .checked_add(content_length)
+        .ok_or(HttpRequestError::TooLarge)?;
+    while data.len() < request_end {
+        match stream.read(&mut buffer)? {
+            0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            bytes_read => data.extend_from_slice(&buffer[..bytes_read]),
+        }
+    }
+    let body = String::from_utf8_lossy(&data[header_end..request_end]).into_owned();
+    Ok(HttpRequest {
+        method,
+        path,
+        query,
+        body,
+    })
+}
+
+pub(crate) fn write_http_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write_http_bytes(stream, status, content_type, body.as_bytes())
+}
+
+fn write_http_bytes(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+        status,
+        content_type,
+        body.len(),
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Placeholder in a template given to `ServerBuilder::index_html`
+const CLIENT_MARKER: &str = "<!-- iwgui -->";
+
+/// Part of the built-in page that is put into custom templates
+fn client_html(page: &str) -> &str {
+    const START: &str = "<!-- iwgui client -->";
+    const END: &str = "<!-- /iwgui client -->";
+    let start = page.find(START).map_or(0, |start| start + START.len());
+    let end = page.rfind(END).unwrap_or(page.len());
+    &page[start..end]
+}
+
+fn handle_incoming_connection(mut stream: TcpStream, shared: Arc<Shared>) {
     let address = stream
         .peer_addr()
         .map(|a| a.to_string())
@@ -253,30 +1498,97 @@ fn handle_incoming_connection(mut stream: TcpStream) {
     info!("Incoming connection from {}", address);
     thread::spawn(move || {
         info!("Created connection thread");
-        let mut buffer = [0; 1024];
-        match stream.read(&mut buffer) {
-            Ok(0) => info!("Zero bytes were read from the stream."),
-            Ok(_bytes_read) => {
-                info!("Read bytes on connection {}", address);
-                let uuid_string = format!("\"{}\"", Uuid::new_v4().to_string());
-                //let contents = include_str!("../web/index.html").replace("#uuid", &uuid_string);
-                let contents = std::fs::read_to_string("web/index.html")
-                    .unwrap()
-                    .replace("#uuid", &uuid_string);
-                let response = format!(
-                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
-                    contents.len(),
-                    contents
-                );
-
-                stream.write(response.as_bytes()).unwrap();
-                stream.flush().unwrap();
-                info!("index.html sent");
+        let request = match read_http_request(&mut stream) {
+            Ok(request) => request,
+            Err(HttpRequestError::TooLarge) => {
+                warn!("Request of connection {} is too large", address);
+                let result =
+                    write_http_response(&mut stream, "413 Payload Too Large", "text/plain", "");
+                if let Err(err) = result {
+                    warn!("Could not respond to connection {}: {}", address, err);
+                }
+                return;
             }
-            Err(err) => panic!(
-                "Could not read from stream of connection {}: {}",
-                address, err
-            ),
+            Err(HttpRequestError::Io(err)) => {
+                warn!("Could not read request of connection {}: {}", address, err);
+                return;
+            }
+        };
+        info!("{} {} from {}", request.method, request.path, address);
+        let result = match (request.method.as_str(), request.path.as_str()) {
+            #[cfg(feature = "webrtc")]
+            ("POST", "/webrtc") => match crate::rtc::answer(&request.body, shared) {
+                Ok(answer) => {
+                    write_http_response(&mut stream, "200 OK", "application/json", &answer)
+                }
+                Err(err) => {
+                    warn!("WebRTC negotiation with {} failed: {}", address, err);
+                    write_http_response(&mut stream, "400 Bad Request", "text/plain", "")
+                }
+            },
+            ("POST", "/poll") => match request.query.get("channel") {
+                Some(channel) => {
+                    // Binary messages are posted hex encoded
+                    if request.query.contains_key("binary") {
+                        Polling::receive_binary(&shared, channel, &request.body);
+                    } else {
+                        Polling::receive(&shared, channel, &request.body);
+                    }
+                    write_http_response(&mut stream, "200 OK", "text/plain", "")
+                }
+                None => write_http_response(&mut stream, "400 Bad Request", "text/plain", ""),
+            },
+            ("GET", "/poll") => {
+                let channel = request.query.get("channel");
+                let seq = request.query.get("seq").and_then(|s| s.parse().ok());
+                match (channel, seq) {
+                    (Some(channel), Some(seq)) => match Polling::poll(&shared, channel, seq) {
+                        Some(batch) => {
+                            write_http_response(&mut stream, "200 OK", "application/json", &batch)
+                        }
+                        None => write_http_response(&mut stream, "404 Not Found", "text/plain", ""),
+                    },
+                    _ => write_http_response(&mut stream, "400 Bad Request", "text/plain", ""),
+                }
+            }
+            #[cfg(feature = "inspector")]
+            ("GET", path) if path.starts_with("/_protocol") => {
+                crate::inspector::respond(&mut stream, &shared, path, &request.query)
+            }
+            #[cfg(feature = "chartjs")]
+            ("GET", "/_iwgui/chart.js") => match &shared.chart_js {
+                Some(source) => {
+                    write_http_response(&mut stream, "200 OK", "text/javascript", source)
+                }
+                None => write_http_response(&mut stream, "404 Not Found", "text/plain", ""),
+            },
+            ("GET", "/favicon.ico") => match &*shared.favicon.lock() {
+                Some((mime, bytes)) => write_http_bytes(&mut stream, "200 OK", mime, bytes),
+                None => write_http_response(&mut stream, "404 Not Found", "text/plain", ""),
+            },
+            ("GET", _) => {
+                let uuid_string = format!("\"{}\"", Uuid::new_v4());
+                #[cfg(feature = "hot-reload")]
+                let contents = &crate::hot_reload::page().unwrap_or_else(|err| {
+                    warn!(
+                        "Could not read the page from disk, serving the built-in one: {}",
+                        err
+                    );
+                    include_str!("../web/index.html").to_owned()
+                });
+                #[cfg(not(feature = "hot-reload"))]
+                let contents = include_str!("../web/index.html");
+                let contents = contents.replacen("#uuid", &uuid_string, 1);
+                let contents = match &shared.index_html {
+                    Some(template) => template.replacen(CLIENT_MARKER, client_html(&contents), 1),
+                    None => contents,
+                };
+                write_http_response(&mut stream, "200 OK", "text/html", &contents)
+            }
+            _ => write_http_response(&mut stream, "404 Not Found", "text/plain", ""),
+        };
+        if let Err(err) = result {
+            warn!("Could not respond to connection {}: {}", address, err);
         }
     });
 }
@@ -0,0 +1,43 @@
+use std::thread;
+use wry::{
+    application::{
+        event::{Event, WindowEvent},
+        event_loop::{ControlFlow, EventLoop},
+        window::WindowBuilder,
+    },
+    webview::WebViewBuilder,
+};
+
+use crate::Server;
+
+impl Server {
+    /// Starts a `Server` on `address` and shows the page in a native window instead of a browser.
+    /// `app` runs on its own thread and gets the `Server` just like `main` does in browser mode.
+    /// Has to be called on the main thread and exits the process when the window is closed.
+    pub fn run_desktop<F>(address: &str, title: &str, app: F) -> !
+    where
+        F: FnOnce(Server) + Send + 'static,
+    {
+        let server = Server::new(address.to_owned());
+        thread::spawn(move || app(server));
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .build(&event_loop)
+            .expect("could not create window");
+        let _webview = WebViewBuilder::new(window)
+            .and_then(|builder| builder.with_url(&format!("http://{}", address)))
+            .and_then(|builder| builder.build())
+            .expect("could not create webview");
+        event_loop.run(move |event, _, control_flow| {
+            *control_flow = ControlFlow::Wait;
+            if let Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } = event
+            {
+                *control_flow = ControlFlow::Exit;
+            }
+        })
+    }
+}
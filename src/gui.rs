@@ -1,35 +1,37 @@
+use base64::prelude::{Engine, BASE64_STANDARD};
 use log::warn;
-use num::{NumCast, ToPrimitive};
-use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::BTreeMap, panic::Location};
+#[cfg(feature = "number")]
+use num::{Bounded, NumCast, ToPrimitive};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fs, mem,
+    panic::Location,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
-#[serde(transparent)]
-pub struct HandleHash(u32);
+#[cfg(feature = "chartjs")]
+use crate::protocol::Dataset;
+pub use crate::protocol::{
+    Align, CalendarMonth, CellEditor, ChartKind, ChartPoint, Decimation, DiffLine, DiffTag,
+    DomEvent, FileEntry, Geometry, HandleHash, Icon, Justify, KeyModifiers, LabelStyle, Style,
+    TableSort, TextSpan, TimelineItem, TreeItem, Width,
+};
+use crate::protocol::{
+    Append, Attributes, Bar, DrawCommand, Element, EventKind, FormField, PlotSeries,
+    ServerBrowserUpdate,
+};
+use crate::session::SavedGui;
+use crate::terminal::TerminalOutput;
+use crate::theme::Theme;
 
-impl HandleHash {
-    fn from_location(location: &Location) -> Self {
-        let file = fxhash::hash32(location.file());
-        let line = fxhash::hash32(&location.line());
-        let column = fxhash::hash32(&location.column());
-        let hash = fxhash::hash32(&(file ^ line ^ column));
-        HandleHash(hash)
-    }
-
-    #[track_caller]
-    fn from_caller() -> Self {
-        Self::from_location(Location::caller())
-    }
-
-    fn from_str<S: AsRef<str>>(s: S) -> Self {
-        HandleHash(fxhash::hash32(s.as_ref()))
-    }
-
-    #[inline]
-    fn combine(h1: Self, h2: Self) -> HandleHash {
-        HandleHash(fxhash::hash32(&(h1.0 ^ h2.0)))
-    }
-}
+#[cfg(feature = "chrono")]
+use chrono::{NaiveDate, NaiveTime};
+#[cfg(feature = "plotters")]
+use plotters::prelude::SVGBackend;
+#[cfg(feature = "plotters")]
+use std::error::Error;
 
 // ----------------------------------------------------------------------------
 // Handle
@@ -81,7 +83,19 @@ struct GuiState {
     events: BTreeMap<HandleHash, Vec<EventKind>>,
     next_id: usize,
     root: Option<HandleHash>,
+    /// Elements rendered above the root, like overlays
+    layers: Vec<HandleHash>,
+    /// Layers created so far at each place, which tells apart the ones created in a loop
+    layer_counts: BTreeMap<HandleHash, usize>,
+    title: Option<String>,
+    theme: Option<Theme>,
     elements: BTreeMap<HandleHash, Element>,
+    scroll_to: Option<HandleHash>,
+    geometries: BTreeMap<HandleHash, Geometry>,
+    context_menu_selections: BTreeMap<HandleHash, usize>,
+    frames: Vec<Frame>,
+    fork: Option<HandleHash>,
+    truncated_from: Option<usize>, // Element count before `Gui::truncate`
 }
 
 impl GuiState {
@@ -105,13 +119,38 @@ pub struct Gui {
 }
 
 impl<'gui> Gui {
-    pub(crate) fn empty(events: BTreeMap<HandleHash, Vec<EventKind>>) -> Self {
+    pub(crate) fn empty(
+        mut events: BTreeMap<HandleHash, Vec<EventKind>>,
+        geometries: BTreeMap<HandleHash, Geometry>,
+    ) -> Self {
+        // Context menus are a modifier, so the builders don't get to see their events
+        let mut context_menu_selections = BTreeMap::new();
+        for (handle_hash, kinds) in &mut events {
+            kinds.retain(|kind| match kind {
+                EventKind::ContextMenuSelected(index) => {
+                    context_menu_selections.insert(*handle_hash, *index);
+                    false
+                }
+                _ => true,
+            });
+        }
+        events.retain(|_, kinds| !kinds.is_empty());
         Self {
             state: RefCell::new(GuiState {
                 events,
                 next_id: 0,
                 root: None,
+                layers: Vec::new(),
+                layer_counts: BTreeMap::new(),
+                title: None,
+                theme: None,
                 elements: BTreeMap::new(),
+                scroll_to: None,
+                geometries,
+                context_menu_selections,
+                frames: Vec::new(),
+                fork: None,
+                truncated_from: None,
             }),
         }
     }
@@ -120,6 +159,151 @@ impl<'gui> Gui {
         self.state.borrow().root.is_none()
     }
 
+    pub(crate) fn save(&self) -> Option<SavedGui> {
+        let state = self.state.borrow();
+        state.root.map(|root| SavedGui {
+            root,
+            layers: state.layers.clone(),
+            title: state.title.clone(),
+            elements: state.elements.clone(),
+        })
+    }
+
+    pub(crate) fn restore(saved: SavedGui) -> Self {
+        let gui = Gui::empty(BTreeMap::new(), BTreeMap::new());
+        {
+            let mut state = gui.state.borrow_mut();
+            state.root = Some(saved.root);
+            state.layers = saved.layers;
+            state.title = saved.title;
+            state.elements = saved.elements;
+        }
+        gui
+    }
+
+    /// Creates an empty `Gui` that receives the same events as this one. Unlike
+    /// the builders it can be moved to another thread, e.g. in a `rayon`
+    /// iterator, and is inserted again with `StackLayout::merge`. Elements
+    /// created in the same place for every fork need a `handle`.
+    #[track_caller]
+    pub fn fork<H: Handle>(&self, handle: &H) -> Gui {
+        let state = self.state.borrow();
+        let gui = Gui::empty(state.events.clone(), state.geometries.clone());
+        {
+            let mut forked = gui.state.borrow_mut();
+            forked.context_menu_selections = state.context_menu_selections.clone();
+            forked.fork = Some(manual_handle(Location::caller(), handle));
+        }
+        gui
+    }
+
+    pub fn element_count(&self) -> usize {
+        self.state.borrow().elements.len()
+    }
+
+    /// Keeps the elements closest to the root so that there are at most `max_elements` and puts
+    /// a label in front that tells how many were left out.
+    pub(crate) fn truncate(&self, max_elements: usize) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let Some(mut root) = state.root else {
+            return;
+        };
+        let warning = HandleHash::from_caller();
+        // The warning of an earlier call is replaced
+        if state.elements.remove(&warning).is_some() {
+            if let Some(Element::StackLayout { children, .. }) = state.elements.get_mut(&root) {
+                children.retain(|c| *c != warning);
+                if let [inner] = children[..] {
+                    if HandleHash::combine(warning, inner) == root {
+                        state.elements.remove(&root);
+                        root = inner;
+                    }
+                }
+            }
+        }
+        let count = *state.truncated_from.get_or_insert(state.elements.len());
+        let mut kept = BTreeSet::from([root]);
+        kept.extend(&state.layers);
+        let mut queue = kept.iter().copied().collect::<VecDeque<_>>();
+        'breadth_first: while let Some(handle_hash) = queue.pop_front() {
+            for child in state.elements[&handle_hash].children() {
+                // One element is left for the warning
+                if kept.len() + 1 >= max_elements {
+                    break 'breadth_first;
+                }
+                kept.insert(child);
+                queue.push_back(child);
+            }
+        }
+        let mut elements = mem::take(&mut state.elements);
+        elements.retain(|handle_hash, _| kept.contains(handle_hash));
+        let mut emptied = Vec::new();
+        for element in elements.values_mut() {
+            match element {
+                Element::StackLayout { children, .. }
+                | Element::WrapLayout { children, .. }
+                | Element::Collapsible { children, .. } => children.retain(|c| kept.contains(c)),
+                element => emptied.extend(
+                    element
+                        .children()
+                        .into_iter()
+                        .filter(|child| !kept.contains(child)),
+                ),
+            }
+        }
+        // Children of the other layouts, e.g. both sides of the columns, have to exist
+        for handle_hash in emptied {
+            elements.insert(
+                handle_hash,
+                Element::StackLayout {
+                    children: Vec::new(),
+                    align: Align::default(),
+                    justify: Justify::default(),
+                    style: None,
+                },
+            );
+        }
+        let text = format!("Only {} of {} elements are shown", elements.len(), count);
+        elements.insert(
+            warning,
+            Element::Label {
+                text,
+                icon: Some(Icon::Warning),
+                style: LabelStyle::default(),
+                attributes: Attributes::default(),
+            },
+        );
+        if let Some(Element::StackLayout { children, .. }) = elements.get_mut(&root) {
+            children.insert(0, warning);
+        } else {
+            let wrapper = HandleHash::combine(warning, root);
+            let children = vec![warning, root];
+            elements.insert(
+                wrapper,
+                Element::StackLayout {
+                    children,
+                    align: Align::default(),
+                    justify: Justify::default(),
+                    style: None,
+                },
+            );
+            root = wrapper;
+        }
+        state.root = Some(root);
+        state.elements = elements;
+        if state.scroll_to.is_some_and(|h| !kept.contains(&h)) {
+            state.scroll_to = None;
+        }
+        state
+            .frames
+            .retain(|frame| kept.contains(&frame.handle_hash));
+    }
+
+    pub(crate) fn take_frames(&self) -> Vec<Frame> {
+        std::mem::take(&mut self.state.borrow_mut().frames)
+    }
+
     fn diff(lhs: &Gui, rhs: &Gui) -> GuiDiff {
         let lhs_state = lhs.state.borrow();
         let rhs_state = rhs.state.borrow();
@@ -128,15 +312,14 @@ impl<'gui> Gui {
         let mut unequal = Vec::new();
         for (lhs_id, lhs_element) in &lhs_state.elements {
             match rhs_state.elements.get(lhs_id) {
-                None => only_lhs.push(lhs_id.clone()),
-                Some(rhs_element) if rhs_element != lhs_element => unequal.push(lhs_id.clone()),
+                None => only_lhs.push(*lhs_id),
+                Some(rhs_element) if rhs_element != lhs_element => unequal.push(*lhs_id),
                 Some(_) => {}
             }
         }
         for rhs_id in rhs_state.elements.keys() {
-            match lhs_state.elements.get(rhs_id) {
-                None => only_rhs.push(rhs_id.clone()),
-                Some(_) => {}
+            if !lhs_state.elements.contains_key(rhs_id) {
+                only_rhs.push(*rhs_id);
             }
         }
         GuiDiff {
@@ -151,7 +334,7 @@ impl<'gui> Gui {
         current_gui: &Gui,
     ) -> ServerBrowserUpdate {
         if let Some(previous_gui) = previous_gui {
-            let diff = Gui::diff(previous_gui, &current_gui);
+            let diff = Gui::diff(previous_gui, current_gui);
             fn to_tuples(
                 handle_hashes: Vec<HandleHash>,
                 gui: &Gui,
@@ -171,47 +354,309 @@ impl<'gui> Gui {
                     .collect()
             }
             let added = to_tuples(diff.only_rhs, current_gui);
-            let updated = to_tuples(diff.unequal, current_gui);
+            let mut updated = to_tuples(diff.unequal, current_gui);
+            // Terminals and line plots only get the lines and points that changed
+            let mut appended = BTreeMap::new();
+            {
+                let previous_state = previous_gui.state.borrow();
+                updated.retain(|handle_hash, element| {
+                    let previous = previous_state.elements.get(handle_hash);
+                    match previous.and_then(|previous| Append::between(previous, element)) {
+                        Some(append) => {
+                            appended.insert(*handle_hash, append);
+                            false
+                        }
+                        None => true,
+                    }
+                });
+            }
             let root = {
                 let gui_root = &current_gui.state.borrow().root;
                 let last_root = &previous_gui.state.borrow().root;
                 if gui_root == last_root {
                     None
                 } else {
-                    gui_root.clone()
+                    *gui_root
+                }
+            };
+            let layers = {
+                let gui_layers = &current_gui.state.borrow().layers;
+                let last_layers = &previous_gui.state.borrow().layers;
+                if gui_layers == last_layers {
+                    None
+                } else {
+                    Some(gui_layers.clone())
+                }
+            };
+            let title = {
+                let gui_title = &current_gui.state.borrow().title;
+                let last_title = &previous_gui.state.borrow().title;
+                if gui_title == last_title {
+                    None
+                } else {
+                    Some(gui_title.clone().unwrap_or_default())
+                }
+            };
+            let theme = {
+                let gui_theme = &current_gui.state.borrow().theme;
+                let last_theme = &previous_gui.state.borrow().theme;
+                if gui_theme == last_theme {
+                    None
+                } else {
+                    Some(gui_theme.clone().unwrap_or_default().css_variables())
                 }
             };
             ServerBrowserUpdate {
                 root,
+                layers,
+                title,
+                theme,
                 added,
                 removed: diff.only_lhs,
                 updated,
+                appended,
+                scroll_to: current_gui.state.borrow().scroll_to,
             }
         } else {
             let state = current_gui.state.borrow();
             ServerBrowserUpdate {
-                root: state.root.clone(),
+                root: state.root,
+                layers: Some(state.layers.clone()),
+                title: state.title.clone(),
+                theme: state.theme.as_ref().map(Theme::css_variables),
                 added: state.elements.clone(),
                 removed: Vec::new(),
                 updated: BTreeMap::new(),
+                appended: BTreeMap::new(),
+                scroll_to: state.scroll_to,
             }
         }
     }
 
     // TODO: Ensure that this works when called multiple times
     #[track_caller]
-    pub fn root(&'gui mut self) -> Indeterminate<'gui> {
+    pub fn root(&'gui self) -> Indeterminate<'gui> {
         let mut state = self.state.borrow_mut();
         // TODO: Move handle functions into one place
         // TODO: Integrate the hash from the parent
-        let handle_hash = HandleHash::from_caller();
+        let handle_hash = match state.fork {
+            Some(fork) => HandleHash::combine(HandleHash::from_caller(), fork),
+            None => HandleHash::from_caller(),
+        };
         state.elements.insert(handle_hash, Element::Indeterminate);
-        if let Some(_) = state.root {
+        if state.root.is_some() {
             panic!("root is already set");
         }
-        state.root = Some(handle_hash.clone());
+        state.root = Some(handle_hash);
         Indeterminate::new(&self.state, handle_hash)
     }
+
+    /// Title of the browser tab. It is only sent when it changed.
+    pub fn title<S: Into<String>>(&self, text: S) {
+        self.state.borrow_mut().title = Some(text.into());
+    }
+
+    /// Layer that floats above the root in the middle of the window, e.g. for a floating
+    /// inspector. Overlays are shown in the order they are created.
+    #[track_caller]
+    pub fn overlay(&'gui self) -> Indeterminate<'gui> {
+        self.layer(HandleHash::from_caller(), |content| Element::Overlay {
+            anchor: None,
+            content,
+        })
+    }
+
+    /// Overlay that is placed below the element `anchor`, e.g. for popovers or dropdown panels.
+    /// The handle hash of an element is written by `Modify::anchor`.
+    #[track_caller]
+    pub fn anchored_overlay(&'gui self, anchor: HandleHash) -> Indeterminate<'gui> {
+        self.layer(HandleHash::from_caller(), |content| Element::Overlay {
+            anchor: Some(anchor),
+            content,
+        })
+    }
+
+    /// Region pinned to the top of the window that stays in place when the page scrolls, e.g.
+    /// for a title and a menu.
+    #[track_caller]
+    pub fn top_bar(&'gui self) -> Indeterminate<'gui> {
+        self.layer(HandleHash::from_caller(), |content| Element::TopBar {
+            content,
+        })
+    }
+
+    /// Region pinned to the bottom of the window that stays in place when the page scrolls,
+    /// e.g. for a status line.
+    #[track_caller]
+    pub fn bottom_bar(&'gui self) -> Indeterminate<'gui> {
+        self.layer(HandleHash::from_caller(), |content| Element::BottomBar {
+            content,
+        })
+    }
+
+    /// Theme of this GUI instead of the one of the connection. It is only sent when it changed.
+    pub fn theme(&self, theme: Theme) {
+        self.state.borrow_mut().theme = Some(theme);
+    }
+
+    /// Panel that floats above the root and can be dragged around by its title, e.g. for tool
+    /// windows. Its position and whether it is open are reported back by the builder.
+    #[track_caller]
+    pub fn window<'value, S: Into<String>>(&'gui self, title: S) -> WindowBuilder<'gui, 'value> {
+        WindowBuilder {
+            gui: self,
+            caller: HandleHash::from_caller(),
+            title: title.into(),
+            open: None,
+            position: None,
+        }
+    }
+
+    /// Keys the user pressed anywhere on the page since the last frame, e.g. for shortcuts. Only
+    /// the `keys` are reported, written like `"Ctrl+S"` or `"Escape"`, and the browser doesn't
+    /// handle them itself. Without `keys`, every key is reported, also the ones typed into text
+    /// boxes.
+    #[track_caller]
+    pub fn on_key<S: AsRef<str>>(&self, keys: &[S]) -> Vec<KeyPress> {
+        let mut state = self.state.borrow_mut();
+        let handle_hash = Self::next_layer_hash(&mut state, HandleHash::from_caller());
+        let mut pressed = Vec::new();
+        for kind in state.events.remove(&handle_hash).into_iter().flatten() {
+            match kind {
+                EventKind::KeyPressed { key, modifiers } => {
+                    pressed.push(KeyPress { key, modifiers })
+                }
+                kind => warn!("wrong event for key listener {:?}: {:?}", handle_hash, kind),
+            }
+        }
+        let keys = keys.iter().map(|key| key.as_ref().to_owned()).collect();
+        state
+            .elements
+            .insert(handle_hash, Element::KeyListener { keys });
+        state.layers.push(handle_hash);
+        pressed
+    }
+
+    /// Hash of the next layer that is created at `caller`. Layers created at the same place,
+    /// e.g. in a loop, are told apart by their order, so layers created elsewhere don't change
+    /// it.
+    fn next_layer_hash(state: &mut GuiState, caller: HandleHash) -> HandleHash {
+        let caller = match state.fork {
+            Some(fork) => HandleHash::combine(caller, fork),
+            None => caller,
+        };
+        let count = state.layer_counts.entry(caller).or_default();
+        let index = *count;
+        *count += 1;
+        HandleHash::combine(caller, HandleHash::from_str(format!("layer{}", index)))
+    }
+
+    fn layer(
+        &'gui self,
+        caller: HandleHash,
+        element: impl FnOnce(HandleHash) -> Element,
+    ) -> Indeterminate<'gui> {
+        let layer_hash = Self::next_layer_hash(&mut self.state.borrow_mut(), caller);
+        self.insert_layer(layer_hash, element)
+    }
+
+    fn insert_layer(
+        &'gui self,
+        layer_hash: HandleHash,
+        element: impl FnOnce(HandleHash) -> Element,
+    ) -> Indeterminate<'gui> {
+        let mut state = self.state.borrow_mut();
+        let content_hash = HandleHash::combine(layer_hash, HandleHash::from_str("content"));
+        state.elements.insert(layer_hash, element(content_hash));
+        state.elements.insert(content_hash, Element::Indeterminate);
+        state.layers.push(layer_hash);
+        Indeterminate::new(&self.state, content_hash)
+    }
+}
+
+/// Key reported by `Gui::on_key`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KeyPress {
+    /// Value of the browser's `KeyboardEvent.key`, e.g. `"s"`, `"S"` or `"Escape"`
+    pub key: String,
+    pub modifiers: KeyModifiers,
+}
+
+// ----------------------------------------------------------------------------
+// WindowBuilder
+// ----------------------------------------------------------------------------
+
+pub struct WindowBuilder<'gui, 'value> {
+    gui: &'gui Gui,
+    caller: HandleHash,
+    title: String,
+    open: Option<&'value mut bool>,
+    position: Option<&'value mut (f64, f64)>,
+}
+
+impl<'gui, 'value> WindowBuilder<'gui, 'value> {
+    /// Adds a close button to the title that sets `open` to false. A closed window isn't
+    /// shown until `open` is set again.
+    pub fn open(mut self, open: &'value mut bool) -> Self {
+        self.open = Some(open);
+        self
+    }
+
+    /// Position of the top left corner in CSS pixels, which is updated when the user drags the
+    /// window. Without it, the browser places the window and only remembers where it was
+    /// dragged to while the window stays on the page.
+    pub fn position(mut self, position: &'value mut (f64, f64)) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Identifies the window by `handle` instead of the place it is created at, e.g. for
+    /// windows that are created in a loop over items that come and go.
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.caller = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Returns the content of the window or `None` if it is closed
+    pub fn finish(mut self) -> Option<Indeterminate<'gui>> {
+        // A closed window keeps its place so that the windows after it keep their hashes
+        let (layer_hash, events) = {
+            let mut state = self.gui.state.borrow_mut();
+            let layer_hash = Gui::next_layer_hash(&mut state, self.caller);
+            (layer_hash, state.events.remove(&layer_hash))
+        };
+        for kind in events.into_iter().flatten() {
+            match kind {
+                EventKind::WindowMoved { x, y } if x.is_finite() && y.is_finite() => {
+                    if let Some(position) = self.position.as_deref_mut() {
+                        *position = (x, y);
+                    }
+                }
+                EventKind::WindowClosed => {
+                    if let Some(open) = self.open.as_deref_mut() {
+                        *open = false;
+                    }
+                }
+                kind => warn!("wrong event for window {:?}: {:?}", self.caller, kind),
+            }
+        }
+        if self.open.as_deref() == Some(&false) {
+            return None;
+        }
+        let title = self.title;
+        let closable = self.open.is_some();
+        let position = self.position.map(|position| *position);
+        Some(
+            self.gui
+                .insert_layer(layer_hash, |content| Element::Window {
+                    title,
+                    position,
+                    closable,
+                    content,
+                }),
+        )
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -220,7 +665,28 @@ impl<'gui> Gui {
 
 pub trait Layout<'gui> {
     fn stacklayout(self) -> StackLayout<'gui>;
+    /// Places the children next to each other and wraps them into the next row when the row is
+    /// full, e.g. for galleries or buttons. `align` places them within their row.
+    fn wrap_layout(self) -> StackLayout<'gui>;
     fn vertical_panels(self) -> (Indeterminate<'gui>, Indeterminate<'gui>);
+    /// `n` columns of the same width.
+    fn columns(self, n: usize) -> Vec<Indeterminate<'gui>>;
+    /// One column per weight. The widths of the columns are relative to the sum of the weights.
+    fn weighted_columns(self, weights: &[f64]) -> Vec<Indeterminate<'gui>>;
+    /// Like `weighted_columns` with handles between the columns that the user can drag. The
+    /// weights are written back to `weights` so that the widths are kept in the next frame.
+    fn resizable_columns(self, weights: &mut [f64]) -> Vec<Indeterminate<'gui>>;
+    /// One column per width, e.g. a sidebar of a fixed width next to a column that takes the
+    /// rest with `[Width::Px(200.0), Width::Weight(1.0)]`.
+    fn sized_columns(self, widths: &[Width]) -> Vec<Indeterminate<'gui>>;
+    /// Tabs with the `titles` of which `selected` is shown. Only the content of the selected tab
+    /// is returned, the other tabs aren't built until they are selected.
+    fn tabs(self, titles: &[&str], selected: &mut usize) -> Indeterminate<'gui>;
+    /// Container that scrolls its content once it is higher than `max_height_px`.
+    fn scroll_area(self, max_height_px: u32) -> Indeterminate<'gui>;
+    /// Like `scroll_area` but the scroll position is written to `scroll_top` while the user
+    /// scrolls. Setting `scroll_top` scrolls the browser.
+    fn tracked_scroll_area(self, max_height_px: u32, scroll_top: &mut f64) -> Indeterminate<'gui>;
 }
 
 pub struct Indeterminate<'gui> {
@@ -232,6 +698,51 @@ impl<'gui> Indeterminate<'gui> {
     fn new(state: &'gui RefCell<GuiState>, handle_hash: HandleHash) -> Self {
         Self { state, handle_hash }
     }
+
+    fn split(self, widths: Vec<Width>, resizable: bool) -> Vec<Indeterminate<'gui>> {
+        let mut state = self.state.borrow_mut();
+        let columns = widths
+            .iter()
+            .map(|_| {
+                let column_hash = HandleHash::combine(
+                    self.handle_hash,
+                    HandleHash::from_str(format!("column{}", state.fetch_id())),
+                );
+                state.elements.insert(column_hash, Element::Indeterminate);
+                column_hash
+            })
+            .collect::<Vec<_>>();
+        let target = state
+            .elements
+            .get_mut(&self.handle_hash)
+            .expect("must be inserted");
+        *target = Element::Columns {
+            columns: columns.clone(),
+            widths,
+            resizable,
+        };
+        columns
+            .into_iter()
+            .map(|column_hash| Indeterminate::new(self.state, column_hash))
+            .collect()
+    }
+
+    fn scroll(self, max_height: u32, scroll_top: Option<f64>) -> Indeterminate<'gui> {
+        let mut state = self.state.borrow_mut();
+        let content_hash =
+            HandleHash::combine(self.handle_hash, HandleHash::from_str("scroll_area"));
+        state.elements.insert(content_hash, Element::Indeterminate);
+        let target = state
+            .elements
+            .get_mut(&self.handle_hash)
+            .expect("must be inserted");
+        *target = Element::ScrollArea {
+            max_height,
+            scroll_top,
+            content: content_hash,
+        };
+        Indeterminate::new(self.state, content_hash)
+    }
 }
 
 impl<'gui> Layout<'gui> for Indeterminate<'gui> {
@@ -239,6 +750,27 @@ impl<'gui> Layout<'gui> for Indeterminate<'gui> {
         let mut state = self.state.borrow_mut();
         let element = Element::StackLayout {
             children: Vec::new(),
+            align: Align::default(),
+            justify: Justify::default(),
+            style: None,
+        };
+        *state
+            .elements
+            .get_mut(&self.handle_hash)
+            .expect("must be inserted") = element;
+        StackLayout {
+            state: self.state,
+            id: self.handle_hash,
+        }
+    }
+
+    fn wrap_layout(self) -> StackLayout<'gui> {
+        let mut state = self.state.borrow_mut();
+        let element = Element::WrapLayout {
+            children: Vec::new(),
+            align: Align::Start,
+            justify: Justify::default(),
+            style: None,
         };
         *state
             .elements
@@ -251,28 +783,87 @@ impl<'gui> Layout<'gui> for Indeterminate<'gui> {
     }
 
     fn vertical_panels(self) -> (Indeterminate<'gui>, Indeterminate<'gui>) {
+        let mut columns = self.columns(2).into_iter();
+        let left = columns.next().expect("two columns");
+        let right = columns.next().expect("two columns");
+        (left, right)
+    }
+
+    fn columns(self, n: usize) -> Vec<Indeterminate<'gui>> {
+        self.weighted_columns(&vec![1.0; n])
+    }
+
+    fn weighted_columns(self, weights: &[f64]) -> Vec<Indeterminate<'gui>> {
+        self.split(weights.iter().copied().map(Width::Weight).collect(), false)
+    }
+
+    fn resizable_columns(self, weights: &mut [f64]) -> Vec<Indeterminate<'gui>> {
+        let events = self.state.borrow_mut().events.remove(&self.handle_hash);
+        for kind in events.into_iter().flatten() {
+            match kind {
+                EventKind::ColumnsResized(resized)
+                    if resized.len() == weights.len()
+                        && resized
+                            .iter()
+                            .all(|weight| weight.is_finite() && *weight > 0.0) =>
+                {
+                    weights.copy_from_slice(&resized)
+                }
+                kind => warn!("wrong event for columns {:?}: {:?}", self.handle_hash, kind),
+            }
+        }
+        self.split(weights.iter().copied().map(Width::Weight).collect(), true)
+    }
+
+    fn sized_columns(self, widths: &[Width]) -> Vec<Indeterminate<'gui>> {
+        self.split(widths.to_vec(), false)
+    }
+
+    fn tabs(self, titles: &[&str], selected: &mut usize) -> Indeterminate<'gui> {
         let mut state = self.state.borrow_mut();
-        let left_hash = HandleHash::combine(
-            self.handle_hash,
-            HandleHash::from_str(format!("left{}", state.fetch_id())),
-        );
-        let right_hash = HandleHash::combine(
+        if let Some(kinds) = state.events.remove(&self.handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TabSelected(index) if index < titles.len() => *selected = index,
+                    kind => warn!("wrong event for tabs {:?}: {:?}", self.handle_hash, kind),
+                }
+            }
+        }
+        *selected = (*selected).min(titles.len().saturating_sub(1));
+        // Every tab has its own content so that switching tabs doesn't morph one into another
+        let content_hash = HandleHash::combine(
             self.handle_hash,
-            HandleHash::from_str(format!("right{}", state.fetch_id())),
+            HandleHash::from_str(format!("tab{}", selected)),
         );
-        state.elements.insert(left_hash, Element::Indeterminate);
-        state.elements.insert(right_hash, Element::Indeterminate);
+        state.elements.insert(content_hash, Element::Indeterminate);
         let target = state
             .elements
             .get_mut(&self.handle_hash)
             .expect("must be inserted");
-        *target = Element::Columns {
-            left: left_hash,
-            right: right_hash,
+        *target = Element::Tabs {
+            titles: titles.iter().map(|title| title.to_string()).collect(),
+            selected: *selected,
+            content: content_hash,
         };
-        let left = Indeterminate::new(self.state, left_hash);
-        let right = Indeterminate::new(self.state, right_hash);
-        (left, right)
+        Indeterminate::new(self.state, content_hash)
+    }
+
+    fn scroll_area(self, max_height_px: u32) -> Indeterminate<'gui> {
+        self.scroll(max_height_px, None)
+    }
+
+    fn tracked_scroll_area(self, max_height_px: u32, scroll_top: &mut f64) -> Indeterminate<'gui> {
+        let events = self.state.borrow_mut().events.remove(&self.handle_hash);
+        for kind in events.into_iter().flatten() {
+            match kind {
+                EventKind::Scrolled(top) if top.is_finite() => *scroll_top = top.max(0.0),
+                kind => warn!(
+                    "wrong event for scroll area {:?}: {:?}",
+                    self.handle_hash, kind
+                ),
+            }
+        }
+        self.scroll(max_height_px, Some(*scroll_top))
     }
 }
 
@@ -286,21 +877,87 @@ pub struct StackLayout<'gui> {
 }
 
 impl<'gui> Elements for StackLayout<'gui> {
-    fn curve_ball(&mut self) -> CurveBall {
+    fn curve_ball(&mut self) -> CurveBall<'_> {
         CurveBall { push_element: self }
     }
 }
 
+impl StackLayout<'_> {
+    pub fn align(self, align: Align) -> Self {
+        match self.state.borrow_mut().elements.get_mut(&self.id) {
+            Some(Element::StackLayout { align: target, .. })
+            | Some(Element::WrapLayout { align: target, .. }) => *target = align,
+            _ => warn!("only stack layouts can be aligned {:?}", self.id),
+        }
+        self
+    }
+
+    pub fn justify(self, justify: Justify) -> Self {
+        match self.state.borrow_mut().elements.get_mut(&self.id) {
+            Some(Element::StackLayout {
+                justify: target, ..
+            })
+            | Some(Element::WrapLayout {
+                justify: target, ..
+            }) => *target = justify,
+            _ => warn!("only stack layouts can be justified {:?}", self.id),
+        }
+        self
+    }
+
+    /// Look of the region the layout covers, e.g. a background and a border for a sidebar. In
+    /// columns, a styled layout fills the height of its column.
+    pub fn style(self, style: Style) -> Self {
+        match self.state.borrow_mut().elements.get_mut(&self.id) {
+            Some(Element::StackLayout { style: target, .. })
+            | Some(Element::WrapLayout { style: target, .. }) => *target = Some(style),
+            _ => warn!("only stack layouts can be styled {:?}", self.id),
+        }
+        self
+    }
+
+    /// Appends the root of a `Gui` created with `Gui::fork`. Its layers are added as well and a
+    /// title or theme set on the fork replaces the one of this `Gui`.
+    pub fn merge(&mut self, gui: Gui) {
+        let mut forked = gui.state.into_inner();
+        let root = forked
+            .root
+            .and_then(|root| Some((root, forked.elements.remove(&root)?)));
+        {
+            let mut state = self.state.borrow_mut();
+            state.elements.append(&mut forked.elements);
+            state.layers.append(&mut forked.layers);
+            state.layer_counts.append(&mut forked.layer_counts);
+            state.frames.append(&mut forked.frames);
+            if forked.scroll_to.is_some() {
+                state.scroll_to = forked.scroll_to;
+            }
+            if forked.title.is_some() {
+                state.title = forked.title;
+            }
+            if forked.theme.is_some() {
+                state.theme = forked.theme;
+            }
+        }
+        match root {
+            Some((root, element)) => self.push_element(root, element),
+            None => warn!("merged a forked gui without a root {:?}", self.id),
+        }
+    }
+}
+
 impl PushElement for StackLayout<'_> {
     fn push_element(&mut self, id: HandleHash, element: Element) {
         let mut state = self.state.borrow_mut();
-        state.elements.insert(id.clone(), element);
+        state.elements.insert(id, element);
         let stacklayout = state
             .elements
             .get_mut(&self.id)
             .expect("must be inserted upon generation of StackLayout");
         match stacklayout {
-            Element::StackLayout { children } => children.push(id),
+            Element::StackLayout { children, .. }
+            | Element::WrapLayout { children, .. }
+            | Element::Collapsible { children, .. } => children.push(id),
             _ => panic!("wrong element inserted"),
         }
     }
@@ -308,10 +965,6 @@ impl PushElement for StackLayout<'_> {
     fn gui(&self) -> &RefCell<GuiState> {
         self.state
     }
-
-    fn handle_hash(&self) -> HandleHash {
-        self.id
-    }
 }
 
 // ----------------------------------------------------------------------------
@@ -322,11 +975,27 @@ pub struct LabelBuilder<'parent> {
     parent: &'parent mut dyn PushElement,
     id: HandleHash,
     text: String,
+    icon: Option<Icon>,
+    style: LabelStyle,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for LabelBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
 }
 
 impl<'parent> LabelBuilder<'parent> {
     fn new(parent: &'parent mut dyn PushElement, id: HandleHash, text: String) -> Self {
-        LabelBuilder { parent, id, text }
+        LabelBuilder {
+            parent,
+            id,
+            text,
+            icon: None,
+            style: LabelStyle::default(),
+            modifiers: Modifiers::default(),
+        }
     }
 
     // TODO: Don't create a handle when the builder is create but only either in a `handle` method or in the `finish` method
@@ -336,64 +1005,350 @@ impl<'parent> LabelBuilder<'parent> {
         self
     }
 
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Shows the text in a font where all characters have the same width, e.g. for code.
+    pub fn monospace(mut self) -> Self {
+        self.style.monospace = true;
+        self
+    }
+
+    /// CSS color of the text, e.g. `"red"` or `"#1e92fe"`
+    pub fn color<S: Into<String>>(mut self, color: S) -> Self {
+        self.style.color = Some(color.into());
+        self
+    }
+
+    /// Font size in CSS pixels
+    pub fn size(mut self, size: f64) -> Self {
+        self.style.size = Some(size);
+        self
+    }
+
+    /// Whether long text continues on the next line, which is the default. Otherwise it is cut
+    /// off with an ellipsis.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.style.wrap = wrap;
+        self
+    }
+
     pub fn finish(self) {
-        self.parent.push_element(self.id, Element::Label(self.text));
+        let attributes = self.modifiers.finish(self.parent.gui(), self.id);
+        self.parent.push_element(
+            self.id,
+            Element::Label {
+                text: self.text,
+                icon: self.icon,
+                style: self.style,
+                attributes,
+            },
+        );
     }
 }
 
 // ----------------------------------------------------------------------------
-// TextboxBuilder
+// RichTextBuilder
 // ----------------------------------------------------------------------------
 
-pub struct TextboxBuilder<'parent, 's> {
+pub struct RichTextBuilder<'parent> {
     parent: &'parent mut dyn PushElement,
     handle_hash: HandleHash,
-    text: &'s mut String,
+    spans: Vec<TextSpan>,
+    modifiers: Modifiers<'parent>,
 }
 
-impl<'parent, 's> TextboxBuilder<'parent, 's> {
-    fn new(parent: &'parent mut dyn PushElement, id: HandleHash, text: &'s mut String) -> Self {
-        TextboxBuilder {
+impl<'parent> Modify<'parent> for RichTextBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> RichTextBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        RichTextBuilder {
             parent,
-            handle_hash: id,
-            text,
+            handle_hash,
+            spans: Vec::new(),
+            modifiers: Modifiers::default(),
         }
     }
 
-    // TODO: Don't create a handle when the builder is create but only either in a `handle` method or in the `finish` method
     #[track_caller]
     pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
         self.handle_hash = manual_handle(Location::caller(), handle);
         self
     }
 
-    pub fn finish(self) {
-        let handle_hash = self.handle_hash;
-        if let Some(kinds) = &mut self.parent.gui().borrow_mut().events.remove(&handle_hash) {
-            for kind in kinds.into_iter() {
-                match kind {
-                    EventKind::TextboxChanged(ref value) => *self.text = value.clone(),
-                    _ => warn!("wrong event for checkbox {:?}: {:?}", handle_hash, kind),
-                }
-            }
-        }
-        self.parent
-            .push_element(handle_hash, Element::Textbox(self.text.clone()));
+    pub fn span(mut self, span: TextSpan) -> Self {
+        self.spans.push(span);
+        self
     }
-}
 
-// ----------------------------------------------------------------------------
-// ButtonBuilder
+    /// Appends `text` without formatting
+    pub fn text<S: Into<String>>(self, text: S) -> Self {
+        self.span(TextSpan::new(text))
+    }
+
+    pub fn bold<S: Into<String>>(self, text: S) -> Self {
+        self.span(TextSpan::new(text).bold())
+    }
+
+    pub fn italic<S: Into<String>>(self, text: S) -> Self {
+        self.span(TextSpan::new(text).italic())
+    }
+
+    pub fn monospace<S: Into<String>>(self, text: S) -> Self {
+        self.span(TextSpan::new(text).monospace())
+    }
+
+    pub fn colored<S: Into<String>, C: Into<String>>(self, text: S, color: C) -> Self {
+        self.span(TextSpan::new(text).color(color))
+    }
+
+    pub fn finish(self) {
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::RichText {
+                spans: self.spans,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TextboxBuilder
+// ----------------------------------------------------------------------------
+
+/// Change of the keyboard focus of an input, see `TextboxBuilder::report_focus`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Focus {
+    Gained,
+    /// The user is done editing, e.g. to validate or store the value
+    Lost,
+}
+
+/// Writes `change` to the output of `report_focus` if there is one
+fn report_focus_change(focus: &mut Option<&mut Option<Focus>>, change: Focus) {
+    if let Some(focus) = focus {
+        **focus = Some(change);
+    }
+}
+
+pub struct TextboxBuilder<'parent, 's> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: &'s mut String,
+    error: Option<String>,
+    focus: Option<&'s mut Option<Focus>>,
+    submitted: Option<&'s mut bool>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for TextboxBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 's> TextboxBuilder<'parent, 's> {
+    fn new(parent: &'parent mut dyn PushElement, id: HandleHash, text: &'s mut String) -> Self {
+        TextboxBuilder {
+            parent,
+            handle_hash: id,
+            text,
+            error: None,
+            focus: None,
+            submitted: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// `submitted` receives whether the user pressed Enter since the last frame, e.g. to act on
+    /// the text once it is complete instead of on every keystroke.
+    pub fn submit_on_enter(mut self, submitted: &'s mut bool) -> Self {
+        self.submitted = Some(submitted);
+        self
+    }
+
+    /// `focus` receives the last change of the keyboard focus since the last frame and is
+    /// `None` otherwise.
+    pub fn report_focus(mut self, focus: &'s mut Option<Focus>) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+
+    /// Message shown below the text box, which is also highlighted, e.g. the result of a
+    /// validation. The text box keeps the focus when the message appears while the user types.
+    pub fn error(mut self, error: Option<String>) -> Self {
+        self.error = error;
+        self
+    }
+
+    /// A disabled text box is greyed out and can't be edited. Enabled by default.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.modifiers.attributes.disabled = !enabled;
+        self
+    }
+
+    // TODO: Don't create a handle when the builder is create but only either in a `handle` method or in the `finish` method
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn finish(mut self) {
+        let handle_hash = self.handle_hash;
+        let report_focus = self.focus.is_some();
+        if let Some(focus) = &mut self.focus {
+            **focus = None;
+        }
+        let submit_on_enter = self.submitted.is_some();
+        if let Some(submitted) = &mut self.submitted {
+            **submitted = false;
+        }
+        let events = self.parent.gui().borrow_mut().events.remove(&handle_hash);
+        // Events that were sent before the element was disabled are dropped
+        if let Some(kinds) = events.filter(|_| !self.modifiers.attributes.disabled) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TextboxChanged(value) => *self.text = value,
+                    EventKind::TextboxSubmitted(value) => {
+                        *self.text = value;
+                        if let Some(submitted) = &mut self.submitted {
+                            **submitted = true;
+                        }
+                    }
+                    EventKind::FocusGained => report_focus_change(&mut self.focus, Focus::Gained),
+                    EventKind::FocusLost => report_focus_change(&mut self.focus, Focus::Lost),
+                    _ => warn!("wrong event for checkbox {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Textbox {
+                text: self.text.clone(),
+                error: self.error,
+                report_focus,
+                submit_on_enter,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// SearchBoxBuilder
+// ----------------------------------------------------------------------------
+
+/// Text box that sends its text once the user stopped typing for the debounce time, instead of
+/// after every keystroke. Enter sends it right away.
+pub struct SearchBoxBuilder<'parent, 's> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: &'s mut String,
+    placeholder: Option<String>,
+    debounce: Duration,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for SearchBoxBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 's> SearchBoxBuilder<'parent, 's> {
+    fn new(parent: &'parent mut dyn PushElement, id: HandleHash, text: &'s mut String) -> Self {
+        SearchBoxBuilder {
+            parent,
+            handle_hash: id,
+            text,
+            placeholder: None,
+            debounce: Duration::from_millis(300),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn placeholder<S: Into<String>>(mut self, placeholder: S) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// How long the user has to stop typing before the text is sent, 300 ms by default.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TextboxChanged(value) => *self.text = value,
+                    _ => warn!("wrong event for search box {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::SearchBox {
+                text: self.text.clone(),
+                placeholder: self.placeholder,
+                debounce_ms: self.debounce.as_millis() as u64,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ButtonBuilder
 // ----------------------------------------------------------------------------
 
 fn manual_handle(location: &Location, handle: &impl Handle) -> HandleHash {
     HandleHash::combine(HandleHash::from_location(location), handle.hash())
 }
 
+/// How a button was pressed, see `ButtonBuilder::finish_press`. Later variants are more
+/// specific, e.g. the clicks of a double click are also reported as clicks.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Press {
+    Click,
+    DoubleClick,
+    /// The button was held down for half a second
+    LongPress,
+}
+
 pub struct ButtonBuilder<'parent> {
     parent: &'parent mut dyn PushElement,
     handle_hash: HandleHash,
     text: Option<String>,
+    icon: Option<Icon>,
+    call_js: Option<String>,
+    shortcut: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for ButtonBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
 }
 
 impl<'parent> ButtonBuilder<'parent> {
@@ -402,6 +1357,10 @@ impl<'parent> ButtonBuilder<'parent> {
             parent,
             handle_hash: id,
             text: None,
+            icon: None,
+            call_js: None,
+            shortcut: None,
+            modifiers: Modifiers::default(),
         }
     }
 
@@ -410,6 +1369,32 @@ impl<'parent> ButtonBuilder<'parent> {
         self
     }
 
+    /// Without a text the button only shows the icon.
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Keys that press the button anywhere on the page, written like `"Ctrl+Enter"`. They are
+    /// shown in the tooltip.
+    pub fn shortcut<S: Into<String>>(mut self, keys: S) -> Self {
+        self.shortcut = Some(keys.into());
+        self
+    }
+
+    /// A disabled button is greyed out and can't be pressed. Enabled by default.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.modifiers.attributes.disabled = !enabled;
+        self
+    }
+
+    /// Calls the JavaScript function registered under `name` in the browser when the button
+    /// is pressed. The result arrives as `ConnectionEvent::JsFunctionReturned`.
+    pub fn call_js<S: Into<String>>(mut self, name: S) -> Self {
+        self.call_js = Some(name.into());
+        self
+    }
+
     // TODO: Don't create a handle when the builder is create but only either in a `handle` method or in the `finish` method
     #[track_caller]
     pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
@@ -418,16 +1403,56 @@ impl<'parent> ButtonBuilder<'parent> {
     }
 
     pub fn finish(self) -> bool {
+        !self.push(false).is_empty()
+    }
+
+    /// Like `finish`, but the browser also reports double clicks and long presses. Returns the
+    /// most specific press since the last frame.
+    pub fn finish_press(self) -> Option<Press> {
         let handle_hash = self.handle_hash;
-        let mut was_pressed = false;
-        if let Some(kinds) = &mut self.parent.gui().borrow_mut().events.remove(&handle_hash) {
-            for _ in kinds.into_iter() {
-                was_pressed = true;
-            }
+        let mut press = None;
+        for kind in self.push(true) {
+            let current = match kind {
+                EventKind::ButtonPressed => Press::Click,
+                EventKind::DoubleClicked => Press::DoubleClick,
+                EventKind::LongPressed => Press::LongPress,
+                kind => {
+                    warn!("wrong event for button {:?}: {:?}", handle_hash, kind);
+                    continue;
+                }
+            };
+            press = press.max(Some(current));
         }
-        self.parent
-            .push_element(handle_hash.clone(), Element::new_button(self.text));
-        return was_pressed;
+        press
+    }
+
+    /// Pushes the element and returns its events
+    fn push(self, gestures: bool) -> Vec<EventKind> {
+        let handle_hash = self.handle_hash;
+        let events = self.parent.gui().borrow_mut().events.remove(&handle_hash);
+        // Events that were sent before the element was disabled are dropped
+        let events = events
+            .filter(|_| !self.modifiers.attributes.disabled)
+            .unwrap_or_default();
+        let mut attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        if let Some(shortcut) = &self.shortcut {
+            attributes.tooltip = Some(match attributes.tooltip {
+                Some(tooltip) => format!("{} ({})", tooltip, shortcut),
+                None => shortcut.clone(),
+            });
+        }
+        self.parent.push_element(
+            handle_hash,
+            Element::new_button(
+                self.text,
+                self.icon,
+                self.call_js,
+                self.shortcut,
+                gestures,
+                attributes,
+            ),
+        );
+        events
     }
 }
 
@@ -440,6 +1465,13 @@ pub struct CheckboxBuilder<'parent, 'value> {
     parent: &'parent mut dyn PushElement,
     handle_hash: HandleHash,
     text: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for CheckboxBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
 }
 
 impl<'parent, 'value> CheckboxBuilder<'parent, 'value> {
@@ -453,6 +1485,7 @@ impl<'parent, 'value> CheckboxBuilder<'parent, 'value> {
             parent,
             handle_hash,
             text: None,
+            modifiers: Modifiers::default(),
         }
     }
 
@@ -461,6 +1494,12 @@ impl<'parent, 'value> CheckboxBuilder<'parent, 'value> {
         self
     }
 
+    /// A disabled checkbox is greyed out and can't be toggled. Enabled by default.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.modifiers.attributes.disabled = !enabled;
+        self
+    }
+
     #[track_caller]
     pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
         self.handle_hash = manual_handle(Location::caller(), handle);
@@ -469,17 +1508,20 @@ impl<'parent, 'value> CheckboxBuilder<'parent, 'value> {
 
     pub fn finish(self) {
         let handle_hash = self.handle_hash;
-        if let Some(kinds) = &mut self.parent.gui().borrow_mut().events.remove(&handle_hash) {
-            for kind in kinds.into_iter() {
+        let events = self.parent.gui().borrow_mut().events.remove(&handle_hash);
+        // Events that were sent before the element was disabled are dropped
+        if let Some(kinds) = events.filter(|_| !self.modifiers.attributes.disabled) {
+            for kind in kinds {
                 match kind {
-                    EventKind::CheckboxChecked(value) => *self.value = *value,
+                    EventKind::CheckboxChecked(value) => *self.value = value,
                     _ => warn!("wrong event for checkbox {:?}: {:?}", handle_hash, kind),
                 }
             }
         }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
         self.parent.push_element(
-            handle_hash.clone(),
-            Element::new_checkbox(self.text, *self.value),
+            handle_hash,
+            Element::new_checkbox(self.text, *self.value, attributes),
         );
     }
 }
@@ -488,35 +1530,53 @@ impl<'parent, 'value> CheckboxBuilder<'parent, 'value> {
 // CheckboxBuilder
 // ----------------------------------------------------------------------------
 
+#[cfg(feature = "number")]
 #[derive(Debug)]
 pub enum ConvertError {
     CouldNotConvertServerValue,
     CouldNotConvertBrowserValue,
 }
 
+#[cfg(feature = "number")]
 pub struct NumberBuilder<'parent, 'value, T> {
     value: &'value mut T,
-    min: Option<i32>,
-    max: Option<i32>,
-    step: Option<i32>,
+    min: Option<f64>,
+    max: Option<f64>,
+    step: Option<f64>,
+    live: bool,
+    error: Option<String>,
+    focus: Option<&'value mut Option<Focus>>,
     parent: &'parent mut dyn PushElement,
     handle_hash: HandleHash,
     text: Option<String>,
+    modifiers: Modifiers<'parent>,
 }
 
+#[cfg(feature = "number")]
+impl<'parent, T> Modify<'parent> for NumberBuilder<'parent, '_, T> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+#[cfg(feature = "number")]
 impl<'parent, 'value, T> NumberBuilder<'parent, 'value, T>
 where
-    T: Copy + NumCast + ToPrimitive,
+    T: Copy + NumCast + ToPrimitive + Bounded,
 {
     fn new(parent: &'parent mut dyn PushElement, id: HandleHash, value: &'value mut T) -> Self {
         NumberBuilder {
             min: None,
             max: None,
             step: None,
+            live: false,
+            error: None,
+            focus: None,
             value,
             parent,
             handle_hash: id,
             text: None,
+            modifiers: Modifiers::default(),
         }
     }
 
@@ -525,108 +1585,3476 @@ where
         self
     }
 
+    /// Smallest value the browser accepts. Smaller values are corrected to it.
+    pub fn min(mut self, min: T) -> Self {
+        self.min = min.to_f64();
+        self
+    }
+
+    /// Largest value the browser accepts. Larger values are corrected to it.
+    pub fn max(mut self, max: T) -> Self {
+        self.max = max.to_f64();
+        self
+    }
+
+    /// Amount the value changes by with the arrows of the browser
+    pub fn step(mut self, step: T) -> Self {
+        self.step = step.to_f64();
+        self
+    }
+
+    /// Sends the value while it is typed or changed with the arrows instead of once it was
+    /// entered.
+    pub fn live(mut self, live: bool) -> Self {
+        self.live = live;
+        self
+    }
+
+    /// Message shown below the number field, which is also highlighted, e.g. the result of a
+    /// validation.
+    pub fn error(mut self, error: Option<String>) -> Self {
+        self.error = error;
+        self
+    }
+
+    /// `focus` receives the last change of the keyboard focus since the last frame and is
+    /// `None` otherwise.
+    pub fn report_focus(mut self, focus: &'value mut Option<Focus>) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+
+    /// A disabled number field is greyed out and can't be edited. Enabled by default.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.modifiers.attributes.disabled = !enabled;
+        self
+    }
+
     #[track_caller]
     pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
         self.handle_hash = manual_handle(Location::caller(), handle);
         self
     }
 
-    pub fn finish(self) -> Result<(), ConvertError> {
+    pub fn finish(mut self) -> Result<(), ConvertError> {
         let handle_hash = self.handle_hash;
-        let element = Element::Number {
-            text: self.text,
-            min: self.min,
-            max: self.max,
-            step: self.step,
-            value: NumCast::from(*self.value).ok_or(ConvertError::CouldNotConvertServerValue)?,
-        };
+        let report_focus = self.focus.is_some();
+        if let Some(focus) = &mut self.focus {
+            **focus = None;
+        }
+        // Integers truncate fractions and unsigned integers can't take negative numbers
+        let integer = T::from(0.5).and_then(|half| half.to_f64()) != Some(0.5);
+        let unsigned = T::from(-1.0).is_none();
+        let min = self.min.or(if unsigned { Some(0.0) } else { None });
         {
-            let events = &mut self.parent.gui().borrow_mut().events;
-            if let Some(kinds) = events.remove(&handle_hash) {
+            let events = self.parent.gui().borrow_mut().events.remove(&handle_hash);
+            // Events that were sent before the element was disabled are dropped
+            if let Some(kinds) = events.filter(|_| !self.modifiers.attributes.disabled) {
                 for kind in kinds {
                     match kind {
                         EventKind::NumberChanged(value) => {
-                            *self.value = NumCast::from(value)
-                                .ok_or(ConvertError::CouldNotConvertBrowserValue)?
+                            let mut value = if integer { value.round() } else { value };
+                            // The browser lets the user type in values out of the range
+                            if let Some(min) = min {
+                                value = value.max(min);
+                            }
+                            if let Some(max) = self.max {
+                                value = value.min(max);
+                            }
+                            // ... and values beyond the range of `T`, which are corrected to its
+                            // bounds
+                            *self.value = NumCast::from(value).unwrap_or(if value > 0.0 {
+                                T::max_value()
+                            } else {
+                                T::min_value()
+                            })
+                        }
+                        EventKind::FocusGained => {
+                            report_focus_change(&mut self.focus, Focus::Gained)
                         }
+                        EventKind::FocusLost => report_focus_change(&mut self.focus, Focus::Lost),
                         _ => warn!("wrong event for number {:?}", kind),
                     }
                 }
             }
         }
-        self.parent.push_element(handle_hash.clone(), element);
+        let value: f64 =
+            NumCast::from(*self.value).ok_or(ConvertError::CouldNotConvertServerValue)?;
+        let element = Element::Number {
+            text: self.text,
+            min,
+            max: self.max,
+            step: self.step,
+            value,
+            integer,
+            live: self.live,
+            error: self.error,
+            report_focus,
+            attributes: self.modifiers.finish(self.parent.gui(), handle_hash),
+        };
+        self.parent.push_element(handle_hash, element);
         Ok(())
     }
 }
 
 // ----------------------------------------------------------------------------
-// traits
+// SliderBuilder
 // ----------------------------------------------------------------------------
 
-pub struct CurveBall<'p> {
-    push_element: &'p mut dyn PushElement,
+pub struct SliderBuilder<'parent, 'value> {
+    value: &'value mut f64,
+    min: f64,
+    max: f64,
+    step: Option<f64>,
+    live: bool,
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: Option<String>,
+    modifiers: Modifiers<'parent>,
 }
 
-trait PushElement {
-    fn push_element(&mut self, id: HandleHash, element: Element);
-    fn handle_hash(&self) -> HandleHash;
-    fn gui(&self) -> &RefCell<GuiState>;
+impl<'parent> Modify<'parent> for SliderBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
 }
 
-pub trait Elements {
-    #[doc(hidden)]
-    fn curve_ball(&mut self) -> CurveBall;
+impl<'parent, 'value> SliderBuilder<'parent, 'value> {
+    fn new(parent: &'parent mut dyn PushElement, id: HandleHash, value: &'value mut f64) -> Self {
+        SliderBuilder {
+            value,
+            min: 0.0,
+            max: 1.0,
+            step: None,
+            live: false,
+            parent,
+            handle_hash: id,
+            text: None,
+            modifiers: Modifiers::default(),
+        }
+    }
 
-    #[track_caller]
-    fn header<S: Into<String>>(&mut self, text: S) {
-        let e = self.curve_ball().push_element;
-        let id = HandleHash::from_caller();
-        e.push_element(id, Element::Header(text.into()))
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = Some(text.to_string());
+        self
     }
 
-    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
     #[track_caller]
-    fn label<T: AsRef<str>>(&mut self, text: T) -> LabelBuilder {
-        let parent = self.curve_ball().push_element;
-        let id = HandleHash::from_caller();
-        LabelBuilder::new(parent, id, text.as_ref().to_string())
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
     }
 
-    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
-    #[track_caller]
-    fn text_box<'s>(&mut self, text: &'s mut String) -> TextboxBuilder<'_, 's> {
-        let parent = self.curve_ball().push_element;
-        let id = HandleHash::from_caller();
-        TextboxBuilder::new(parent, id, text)
+    /// Defaults to 0.
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = min;
+        self
     }
 
-    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
-    #[track_caller]
-    fn button(&mut self) -> ButtonBuilder {
-        let parent = self.curve_ball().push_element;
-        let id = HandleHash::from_caller();
-        ButtonBuilder::new(parent, id)
+    /// Defaults to 1.
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = max;
+        self
     }
 
-    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
-    #[track_caller]
-    fn checkbox<'value>(&mut self, value: &'value mut bool) -> CheckboxBuilder<'_, 'value> {
-        let parent = self.curve_ball().push_element;
-        let id = HandleHash::from_caller();
-        CheckboxBuilder::new(parent, id, value)
+    /// Without a step every value between `min` and `max` can be picked.
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
     }
 
-    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
-    #[track_caller]
-    fn number<'value, T>(&mut self, value: &'value mut T) -> NumberBuilder<'_, 'value, T>
-    where
-        T: Copy + NumCast + ToPrimitive,
+    /// Sends the value while the slider is dragged instead of when it is released.
+    pub fn live(mut self, live: bool) -> Self {
+        self.live = live;
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::SliderChanged(value) if value.is_finite() => {
+                        *self.value = value.max(self.min).min(self.max)
+                    }
+                    _ => warn!("wrong event for slider {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let element = Element::Slider {
+            text: self.text,
+            min: self.min,
+            max: self.max,
+            step: self.step,
+            value: *self.value,
+            live: self.live,
+            attributes: self.modifiers.finish(self.parent.gui(), handle_hash),
+        };
+        self.parent.push_element(handle_hash, element);
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ColorBuilder
+// ----------------------------------------------------------------------------
+
+pub struct ColorBuilder<'parent, 'value> {
+    rgb: &'value mut [u8; 3],
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for ColorBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> ColorBuilder<'parent, 'value> {
+    fn new(parent: &'parent mut dyn PushElement, id: HandleHash, rgb: &'value mut [u8; 3]) -> Self {
+        ColorBuilder {
+            rgb,
+            parent,
+            handle_hash: id,
+            text: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::ColorChanged(rgb) => *self.rgb = rgb,
+                    _ => warn!("wrong event for color {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let element = Element::Color {
+            text: self.text,
+            rgb: *self.rgb,
+            attributes: self.modifiers.finish(self.parent.gui(), handle_hash),
+        };
+        self.parent.push_element(handle_hash, element);
+    }
+}
+
+// ----------------------------------------------------------------------------
+// DateBuilder
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "chrono")]
+pub struct DateBuilder<'parent, 'value> {
+    date: &'value mut NaiveDate,
+    min: Option<NaiveDate>,
+    max: Option<NaiveDate>,
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+#[cfg(feature = "chrono")]
+impl<'parent> Modify<'parent> for DateBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<'parent, 'value> DateBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        id: HandleHash,
+        date: &'value mut NaiveDate,
+    ) -> Self {
+        DateBuilder {
+            date,
+            min: None,
+            max: None,
+            parent,
+            handle_hash: id,
+            text: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn min(mut self, min: NaiveDate) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: NaiveDate) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match &kind {
+                    EventKind::DateChanged(date) => match date.parse::<NaiveDate>() {
+                        Ok(date)
+                            if self.min.is_none_or(|min| date >= min)
+                                && self.max.is_none_or(|max| date <= max) =>
+                        {
+                            *self.date = date
+                        }
+                        _ => warn!("invalid date for date {:?}: {:?}", handle_hash, date),
+                    },
+                    _ => warn!("wrong event for date {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let element = Element::Date {
+            text: self.text,
+            min: self.min.map(|min| min.to_string()),
+            max: self.max.map(|max| max.to_string()),
+            date: self.date.to_string(),
+            attributes: self.modifiers.finish(self.parent.gui(), handle_hash),
+        };
+        self.parent.push_element(handle_hash, element);
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TimeBuilder
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "chrono")]
+pub struct TimeBuilder<'parent, 'value> {
+    time: &'value mut NaiveTime,
+    seconds: bool,
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+#[cfg(feature = "chrono")]
+impl<'parent> Modify<'parent> for TimeBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<'parent, 'value> TimeBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        id: HandleHash,
+        time: &'value mut NaiveTime,
+    ) -> Self {
+        TimeBuilder {
+            time,
+            seconds: false,
+            parent,
+            handle_hash: id,
+            text: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Lets the user enter seconds as well as hours and minutes.
+    pub fn seconds(mut self) -> Self {
+        self.seconds = true;
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match &kind {
+                    EventKind::TimeChanged(time) => match time.parse::<NaiveTime>() {
+                        Ok(time) => *self.time = time,
+                        Err(_) => warn!("invalid time for time {:?}: {:?}", handle_hash, time),
+                    },
+                    _ => warn!("wrong event for time {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let element = Element::Time {
+            text: self.text,
+            seconds: self.seconds,
+            time: self.time.to_string(),
+            attributes: self.modifiers.finish(self.parent.gui(), handle_hash),
+        };
+        self.parent.push_element(handle_hash, element);
+    }
+}
+
+// ----------------------------------------------------------------------------
+// FileUploadBuilder
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Lets the user pick files that are sent to the server. Their size is capped by
+/// `Limits::max_upload_bytes` and `Limits::max_upload_bytes_in_flight`.
+pub struct FileUploadBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: Option<String>,
+    accept: Option<String>,
+    multiple: bool,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for FileUploadBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> FileUploadBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, id: HandleHash) -> Self {
+        FileUploadBuilder {
+            parent,
+            handle_hash: id,
+            text: None,
+            accept: None,
+            multiple: false,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// File types the browser offers, e.g. `".png,.jpg"` or `"image/*"`.
+    pub fn accept<S: Into<String>>(mut self, accept: S) -> Self {
+        self.accept = Some(accept.into());
+        self
+    }
+
+    /// Lets the user pick several files at once.
+    pub fn multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    /// Returns the files that arrived completely since the last frame.
+    pub fn finish(self) -> Vec<UploadedFile> {
+        let handle_hash = self.handle_hash;
+        let mut uploaded = Vec::new();
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::FileUploaded { name, bytes } => {
+                        uploaded.push(UploadedFile { name, bytes })
+                    }
+                    _ => warn!("wrong event for file upload {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let element = Element::FileUpload {
+            text: self.text,
+            accept: self.accept,
+            multiple: self.multiple,
+            attributes: self.modifiers.finish(self.parent.gui(), handle_hash),
+        };
+        self.parent.push_element(handle_hash, element);
+        uploaded
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ListBuilder
+// ----------------------------------------------------------------------------
+
+pub struct ListBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    selected: &'value mut Option<usize>,
+    double_clicked: Option<&'value mut Option<usize>>,
+    long_pressed: Option<&'value mut Option<usize>>,
+    items: Vec<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for ListBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> ListBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        selected: &'value mut Option<usize>,
+    ) -> Self {
+        ListBuilder {
+            parent,
+            handle_hash,
+            selected,
+            double_clicked: None,
+            long_pressed: None,
+            items: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn item<S: Into<String>>(mut self, text: S) -> Self {
+        self.items.push(text.into());
+        self
+    }
+
+    pub fn items<I: IntoIterator<Item = S>, S: Into<String>>(mut self, items: I) -> Self {
+        self.items.extend(items.into_iter().map(Into::into));
+        self
+    }
+
+    /// `item` receives the index of the item that was double clicked or activated with Enter
+    /// since the last frame and is `None` otherwise. The item is also selected.
+    pub fn double_clicked(mut self, item: &'value mut Option<usize>) -> Self {
+        self.double_clicked = Some(item);
+        self
+    }
+
+    /// `item` receives the index of the item that was held down for half a second since the
+    /// last frame and is `None` otherwise. The item is also selected.
+    pub fn long_pressed(mut self, item: &'value mut Option<usize>) -> Self {
+        self.long_pressed = Some(item);
+        self
+    }
+
+    pub fn finish(mut self) {
+        let handle_hash = self.handle_hash;
+        for item in self.double_clicked.iter_mut().chain(&mut self.long_pressed) {
+            **item = None;
+        }
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::ListSelected(index) if index < self.items.len() => {
+                        *self.selected = Some(index)
+                    }
+                    // Both apply to the item that was selected before
+                    EventKind::DoubleClicked => {
+                        if let Some(item) = self.double_clicked.as_deref_mut() {
+                            *item = *self.selected;
+                        }
+                    }
+                    EventKind::LongPressed => {
+                        if let Some(item) = self.long_pressed.as_deref_mut() {
+                            *item = *self.selected;
+                        }
+                    }
+                    _ => warn!("wrong event for list {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::List {
+                items: self.items,
+                selected: *self.selected,
+                gestures: self.double_clicked.is_some() || self.long_pressed.is_some(),
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ReorderableListBuilder
+// ----------------------------------------------------------------------------
+
+pub struct ReorderableListBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    items: Vec<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for ReorderableListBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> ReorderableListBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        ReorderableListBuilder {
+            parent,
+            handle_hash,
+            items: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn item<S: Into<String>>(mut self, text: S) -> Self {
+        self.items.push(text.into());
+        self
+    }
+
+    pub fn items<I: IntoIterator<Item = S>, S: Into<String>>(mut self, items: I) -> Self {
+        self.items.extend(items.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns the `(from, to)` of the moves since the last frame in the order they happened. The
+    /// backing `Vec` follows a move with `let item = items.remove(from); items.insert(to, item);`.
+    pub fn finish(self) -> Vec<(usize, usize)> {
+        let handle_hash = self.handle_hash;
+        let mut moved = Vec::new();
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::ItemMoved { from, to }
+                        if from < self.items.len() && to < self.items.len() =>
+                    {
+                        moved.push((from, to))
+                    }
+                    kind => warn!(
+                        "wrong event for reorderable list {:?}: {:?}",
+                        handle_hash, kind
+                    ),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::ReorderableList {
+                items: self.items,
+                attributes,
+            },
+        );
+        moved
+    }
+}
+
+// ----------------------------------------------------------------------------
+// DropdownBuilder
+// ----------------------------------------------------------------------------
+
+/// Select box whose selection is the index of an option. For an enum the options are usually
+/// the names of its variants in the order of an array of the variants.
+pub struct DropdownBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    selected: &'value mut usize,
+    options: Vec<String>,
+    text: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for DropdownBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> DropdownBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        selected: &'value mut usize,
+    ) -> Self {
+        DropdownBuilder {
+            parent,
+            handle_hash,
+            selected,
+            options: Vec::new(),
+            text: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub fn option<S: Into<String>>(mut self, text: S) -> Self {
+        self.options.push(text.into());
+        self
+    }
+
+    pub fn options<I: IntoIterator<Item = S>, S: Into<String>>(mut self, options: I) -> Self {
+        self.options.extend(options.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::DropdownChanged(index) if index < self.options.len() => {
+                        *self.selected = index
+                    }
+                    _ => warn!("wrong event for dropdown {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Dropdown {
+                text: self.text,
+                options: self.options,
+                selected: *self.selected,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TreeBuilder
+// ----------------------------------------------------------------------------
+
+fn tree_contains(items: &[TreeItem], path: &[usize]) -> bool {
+    match path.split_first() {
+        None => false,
+        Some((index, [])) => *index < items.len(),
+        Some((index, rest)) => items
+            .get(*index)
+            .is_some_and(|item| tree_contains(item.children(), rest)),
+    }
+}
+
+/// Tree whose selection is the path of child indices to the selected item.
+pub struct TreeBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    selected: &'value mut Option<Vec<usize>>,
+    items: Vec<TreeItem>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for TreeBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> TreeBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        selected: &'value mut Option<Vec<usize>>,
+    ) -> Self {
+        TreeBuilder {
+            parent,
+            handle_hash,
+            selected,
+            items: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn item(mut self, item: TreeItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TreeSelected(path) if tree_contains(&self.items, &path) => {
+                        *self.selected = Some(path)
+                    }
+                    _ => warn!("wrong event for tree {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Tree {
+                items: self.items,
+                selected: self.selected.clone(),
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TimelineBuilder
+// ----------------------------------------------------------------------------
+
+/// Bars of tasks or intervals on a shared time axis. Rescheduling by dragging changes `items`.
+pub struct TimelineBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    items: &'value mut [TimelineItem],
+    editable: bool,
+    snap: Option<f64>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for TimelineBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> TimelineBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        items: &'value mut [TimelineItem],
+    ) -> Self {
+        TimelineBuilder {
+            parent,
+            handle_hash,
+            items,
+            editable: false,
+            snap: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Lets the user move bars by dragging them and change their end by dragging their right
+    /// edge.
+    pub fn editable(mut self) -> Self {
+        self.editable = true;
+        self
+    }
+
+    /// Rounds the times of dragged bars to multiples of `step`.
+    pub fn snap(mut self, step: f64) -> Self {
+        self.snap = Some(step);
+        self
+    }
+
+    /// Returns the index of the item the user clicked since the last frame.
+    pub fn finish(self) -> Option<usize> {
+        let handle_hash = self.handle_hash;
+        let mut clicked = None;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TimelineClicked(index) if index < self.items.len() => {
+                        clicked = Some(index)
+                    }
+                    EventKind::TimelineMoved { index, start, end }
+                        if self.editable && index < self.items.len() && start <= end =>
+                    {
+                        self.items[index].start = start;
+                        self.items[index].end = end;
+                    }
+                    _ => warn!("wrong event for timeline {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Timeline {
+                items: self.items.to_vec(),
+                editable: self.editable,
+                snap: self.snap,
+                attributes,
+            },
+        );
+        clicked
+    }
+}
+
+// ----------------------------------------------------------------------------
+// CalendarBuilder
+// ----------------------------------------------------------------------------
+
+/// Grid of the days of a month with buttons to go to the previous and next month, which change
+/// `month`.
+pub struct CalendarBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    month: &'value mut CalendarMonth,
+    days: BTreeMap<u32, Vec<String>>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for CalendarBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> CalendarBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        month: &'value mut CalendarMonth,
+    ) -> Self {
+        CalendarBuilder {
+            parent,
+            handle_hash,
+            month,
+            days: BTreeMap::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Shows `badge` in the cell of `day` of the month, e.g. the title of an appointment. A day
+    /// can have several badges.
+    pub fn badge<S: Into<String>>(mut self, day: u32, badge: S) -> Self {
+        self.days.entry(day).or_default().push(badge.into());
+        self
+    }
+
+    /// Returns the day of the month the user clicked since the last frame.
+    pub fn finish(self) -> Option<u32> {
+        let handle_hash = self.handle_hash;
+        let mut clicked = None;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::CalendarDayClicked(day)
+                        if (1..=self.month.days()).contains(&day) =>
+                    {
+                        clicked = Some(day)
+                    }
+                    EventKind::CalendarMonthChanged(month) if (1..=12).contains(&month.month) => {
+                        *self.month = month;
+                        clicked = None;
+                    }
+                    _ => warn!("wrong event for calendar {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        let month = *self.month;
+        self.parent.push_element(
+            handle_hash,
+            Element::Calendar {
+                month,
+                days: self
+                    .days
+                    .into_iter()
+                    .filter(|(day, _)| (1..=month.days()).contains(day))
+                    .collect(),
+                attributes,
+            },
+        );
+        clicked
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TableBuilder
+// ----------------------------------------------------------------------------
+
+/// Text the user entered into the cell in `row` and `column` of a table. `row` is the index
+/// passed to `TableBuilder::indexed_row`, or the position of the row otherwise.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CellEdit {
+    pub row: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+pub struct TableBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    columns: Vec<String>,
+    editors: Vec<Option<CellEditor>>,
+    rows: Vec<Vec<String>>,
+    /// Index of each row in the data of the caller
+    indices: Vec<usize>,
+    sort: Option<&'value mut Option<TableSort>>,
+    filter: Option<&'value mut String>,
+    selected: Option<&'value mut Option<usize>>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for TableBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> TableBuilder<'parent, 'value> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        TableBuilder {
+            parent,
+            handle_hash,
+            columns: Vec::new(),
+            editors: Vec::new(),
+            rows: Vec::new(),
+            indices: Vec::new(),
+            sort: None,
+            filter: None,
+            selected: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn column<S: Into<String>>(mut self, header: S) -> Self {
+        self.columns.push(header.into());
+        self.editors.push(None);
+        self
+    }
+
+    /// Column whose cells are shown as inputs. Edits arrive for the table as a whole rather than
+    /// for each cell and are returned by `finish` with the index of the row, so they can be
+    /// written back to the data of the rows. Use `indexed_row` when the rows are sorted or
+    /// filtered.
+    pub fn editable_column<S: Into<String>>(mut self, header: S, editor: CellEditor) -> Self {
+        self.columns.push(header.into());
+        self.editors.push(Some(editor));
+        self
+    }
+
+    /// Cells of a row from left to right. Rows may have fewer or more cells than there are columns.
+    pub fn row<I, S>(self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let index = self.rows.len();
+        self.indexed_row(index, cells)
+    }
+
+    /// Like `row` but edits and the selection refer to the row by `index`, e.g. its index in
+    /// the data before it was sorted or filtered.
+    pub fn indexed_row<I, S>(mut self, index: usize, cells: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+        self.indices.push(index);
+        self
+    }
+
+    /// Makes the headers clickable. A click on a header sorts by its column, a second click
+    /// reverses the order. The rows are shown as they are passed, so they have to be sorted by
+    /// `sort` before they are added.
+    pub fn sort(mut self, sort: &'value mut Option<TableSort>) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// Shows a text box above the table whose text is written to `filter`. The rows are shown
+    /// as they are passed, so they have to be filtered before they are added.
+    pub fn filter(mut self, filter: &'value mut String) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Makes the rows selectable by a click. `selected` is the index of the row, see
+    /// `indexed_row`.
+    pub fn selected(mut self, selected: &'value mut Option<usize>) -> Self {
+        self.selected = Some(selected);
+        self
+    }
+
+    /// Returns the cells that were edited since the last frame.
+    pub fn finish(mut self) -> Vec<CellEdit> {
+        let handle_hash = self.handle_hash;
+        let mut edits = Vec::new();
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TableCellEdited { row, column, text }
+                        if row < self.rows.len()
+                            && matches!(self.editors.get(column), Some(Some(_))) =>
+                    {
+                        edits.push(CellEdit {
+                            row: self.indices[row],
+                            column,
+                            text,
+                        });
+                    }
+                    EventKind::TableSortRequested(column)
+                        if column < self.columns.len() && self.sort.is_some() =>
+                    {
+                        if let Some(sort) = self.sort.as_deref_mut() {
+                            *sort = match *sort {
+                                Some(sort) if sort.column == column => Some(TableSort {
+                                    column,
+                                    descending: !sort.descending,
+                                }),
+                                _ => Some(TableSort {
+                                    column,
+                                    descending: false,
+                                }),
+                            };
+                        }
+                    }
+                    EventKind::TableFilterChanged(text) if self.filter.is_some() => {
+                        if let Some(filter) = self.filter.as_deref_mut() {
+                            *filter = text;
+                        }
+                    }
+                    EventKind::TableRowClicked(row)
+                        if row < self.rows.len() && self.selected.is_some() =>
+                    {
+                        if let Some(selected) = self.selected.as_deref_mut() {
+                            *selected = Some(self.indices[row]);
+                        }
+                    }
+                    kind => warn!("wrong event for table {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        // The browser highlights the selected row by its position
+        let selected = self
+            .selected
+            .as_deref()
+            .copied()
+            .flatten()
+            .and_then(|selected| self.indices.iter().position(|i| *i == selected));
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Table {
+                columns: self.columns,
+                editors: self.editors,
+                rows: self.rows,
+                sortable: self.sort.is_some(),
+                sort: self.sort.as_deref().copied().flatten(),
+                filter: self.filter.as_deref().cloned(),
+                selectable: self.selected.is_some(),
+                selected,
+                attributes,
+            },
+        );
+        edits
+    }
+}
+
+// ----------------------------------------------------------------------------
+// PaginationBuilder
+// ----------------------------------------------------------------------------
+
+pub struct PaginationBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    current: &'value mut usize,
+    total_pages: usize,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for PaginationBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> PaginationBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        current: &'value mut usize,
+        total_pages: usize,
+    ) -> Self {
+        PaginationBuilder {
+            parent,
+            handle_hash,
+            current,
+            total_pages,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::PageSelected(page) if page < self.total_pages => {
+                        *self.current = page
+                    }
+                    _ => warn!("wrong event for pagination {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        // The number of pages shrinks when the paged list does
+        *self.current = (*self.current).min(self.total_pages.saturating_sub(1));
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Pagination {
+                current: *self.current,
+                total_pages: self.total_pages,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// CopyableBuilder
+// ----------------------------------------------------------------------------
+
+/// Text with a button that copies it to the clipboard in the browser.
+pub struct CopyableBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    text: String,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for CopyableBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> CopyableBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash, text: String) -> Self {
+        CopyableBuilder {
+            parent,
+            handle_hash,
+            text,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Returns whether the text was copied since the last frame.
+    pub fn finish(self) -> bool {
+        let handle_hash = self.handle_hash;
+        let mut copied = false;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::Copied => copied = true,
+                    _ => warn!("wrong event for copyable {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Copyable {
+                text: self.text,
+                attributes,
+            },
+        );
+        copied
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TagsBuilder
+// ----------------------------------------------------------------------------
+
+/// Removable chips with a text box to add more. Empty tags and tags that already exist are not
+/// added.
+pub struct TagsBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    tags: &'value mut Vec<String>,
+    placeholder: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for TagsBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> TagsBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        tags: &'value mut Vec<String>,
+    ) -> Self {
+        TagsBuilder {
+            parent,
+            handle_hash,
+            tags,
+            placeholder: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn placeholder<S: Into<String>>(mut self, placeholder: S) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TagAdded(tag) => {
+                        let tag = tag.trim();
+                        if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+                            self.tags.push(tag.to_owned());
+                        }
+                    }
+                    EventKind::TagRemoved(index) if index < self.tags.len() => {
+                        self.tags.remove(index);
+                    }
+                    _ => warn!("wrong event for tags {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Tags {
+                tags: self.tags.clone(),
+                placeholder: self.placeholder,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// BreadcrumbsBuilder
+// ----------------------------------------------------------------------------
+
+pub struct BreadcrumbsBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    segments: Vec<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for BreadcrumbsBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> BreadcrumbsBuilder<'parent> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        segments: Vec<String>,
+    ) -> Self {
+        BreadcrumbsBuilder {
+            parent,
+            handle_hash,
+            segments,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Returns the index of the segment that was clicked since the last frame.
+    pub fn finish(self) -> Option<usize> {
+        let handle_hash = self.handle_hash;
+        let mut clicked = None;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::BreadcrumbClicked(index) if index < self.segments.len() => {
+                        clicked = Some(index)
+                    }
+                    _ => warn!("wrong event for breadcrumbs {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Breadcrumbs {
+                segments: self.segments,
+                attributes,
+            },
+        );
+        clicked
+    }
+}
+
+// ----------------------------------------------------------------------------
+// DiffViewBuilder
+// ----------------------------------------------------------------------------
+
+fn diff_lines(old: &str, new: &str, context: Option<usize>) -> Vec<DiffLine> {
+    let diff = similar::TextDiff::from_lines(old, new);
+    let groups = match context {
+        Some(context) => diff.grouped_ops(context),
+        None => vec![diff.ops().to_vec()],
+    };
+    let mut lines = Vec::new();
+    for (index, group) in groups.iter().enumerate() {
+        if index > 0 {
+            lines.push(DiffLine {
+                tag: DiffTag::Skipped,
+                old_line: None,
+                new_line: None,
+                text: String::new(),
+            });
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                lines.push(DiffLine {
+                    tag: match change.tag() {
+                        similar::ChangeTag::Equal => DiffTag::Equal,
+                        similar::ChangeTag::Delete => DiffTag::Delete,
+                        similar::ChangeTag::Insert => DiffTag::Insert,
+                    },
+                    old_line: change.old_index().map(|index| index + 1),
+                    new_line: change.new_index().map(|index| index + 1),
+                    text: change
+                        .value()
+                        .trim_end_matches(&['\r', '\n'][..])
+                        .to_owned(),
+                });
+            }
+        }
+    }
+    lines
+}
+
+/// Line by line diff of two texts, shown side by side by default.
+pub struct DiffViewBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    old: &'value str,
+    new: &'value str,
+    unified: bool,
+    context: Option<usize>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for DiffViewBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> DiffViewBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        old: &'value str,
+        new: &'value str,
+    ) -> Self {
+        DiffViewBuilder {
+            parent,
+            handle_hash,
+            old,
+            new,
+            unified: false,
+            context: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Shows deleted and inserted lines below each other in one column.
+    pub fn unified(mut self) -> Self {
+        self.unified = true;
+        self
+    }
+
+    /// Only shows `lines` unchanged lines around every change.
+    pub fn context(mut self, lines: usize) -> Self {
+        self.context = Some(lines);
+        self
+    }
+
+    pub fn finish(self) {
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::DiffView {
+                lines: diff_lines(self.old, self.new, self.context),
+                unified: self.unified,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// JsonViewBuilder
+// ----------------------------------------------------------------------------
+
+/// Collapsible tree of the keys and values of a JSON value. Which nodes the user expanded is
+/// kept by the browser.
+pub struct JsonViewBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    value: serde_json::Value,
+    expanded: usize,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for JsonViewBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> JsonViewBuilder<'parent> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        value: serde_json::Value,
+    ) -> Self {
+        JsonViewBuilder {
+            parent,
+            handle_hash,
+            value,
+            expanded: 1,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Number of levels that are expanded until the user changes it. Defaults to 1.
+    pub fn expanded(mut self, depth: usize) -> Self {
+        self.expanded = depth;
+        self
+    }
+
+    pub fn finish(self) {
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::JsonView {
+                value: self.value,
+                expanded: self.expanded,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// FileBrowserBuilder
+// ----------------------------------------------------------------------------
+
+/// Directory a file browser shows and the file selected in it, relative to the root of the file
+/// browser.
+#[derive(Debug, Default, Clone)]
+pub struct FileBrowserState {
+    directory: Vec<String>,
+    selected: Option<Vec<String>>,
+}
+
+impl FileBrowserState {
+    /// Directory that is shown, relative to the root
+    pub fn directory(&self) -> PathBuf {
+        self.directory.iter().collect()
+    }
+
+    /// Selected file, relative to the root
+    pub fn selected(&self) -> Option<PathBuf> {
+        self.selected
+            .as_ref()
+            .map(|components| components.iter().collect())
+    }
+}
+
+/// Resolves `components` below `root` and makes sure that the result doesn't leave `root`, also
+/// not through symbolic links.
+fn jailed_path(root: &Path, components: &[String]) -> Option<PathBuf> {
+    let valid = |component: &String| {
+        !component.is_empty()
+            && component != "."
+            && component != ".."
+            && !component.contains(['/', '\\'].as_ref())
+    };
+    if !components.iter().all(valid) {
+        return None;
+    }
+    let root = root.canonicalize().ok()?;
+    let path = components
+        .iter()
+        .fold(root.clone(), |path, component| path.join(component))
+        .canonicalize()
+        .ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+/// Files of a directory below `root` with breadcrumbs to go up again. The browser only ever
+/// sees paths relative to `root` and can't leave it.
+pub struct FileBrowserBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    root: &'value Path,
+    state: &'value mut FileBrowserState,
+    extensions: Option<Vec<String>>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for FileBrowserBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> FileBrowserBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        root: &'value Path,
+        state: &'value mut FileBrowserState,
+    ) -> Self {
+        FileBrowserBuilder {
+            parent,
+            handle_hash,
+            root,
+            state,
+            extensions: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Only shows and allows to select files with one of `extensions`, e.g. `["toml", "json"]`.
+    pub fn extensions<I: IntoIterator<Item = S>, S: Into<String>>(mut self, extensions: I) -> Self {
+        self.extensions = Some(extensions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn allowed(&self, path: &Path) -> bool {
+        match &self.extensions {
+            None => true,
+            Some(extensions) => path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extensions.iter().any(|e| e == extension)),
+        }
+    }
+
+    fn entries(&self, directory: &Path) -> Result<Vec<FileEntry>, String> {
+        let root = self.root.canonicalize().map_err(|err| err.to_string())?;
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(directory).map_err(|err| err.to_string())? {
+            let Ok(entry) = entry else { continue };
+            // Links that lead out of the root are left out
+            let inside = entry
+                .path()
+                .canonicalize()
+                .is_ok_and(|path| path.starts_with(&root));
+            if !inside {
+                continue;
+            }
+            let Ok(metadata) = entry.path().metadata() else {
+                continue;
+            };
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if metadata.is_dir() || self.allowed(&entry.path()) {
+                entries.push(FileEntry {
+                    name,
+                    directory: metadata.is_dir(),
+                    size: metadata.is_file().then_some(metadata.len()),
+                });
+            }
+        }
+        entries.sort_by(|a, b| {
+            b.directory
+                .cmp(&a.directory)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(entries)
+    }
+
+    /// Returns the absolute path of the file the user selected since the last frame.
+    pub fn finish(self) -> Option<PathBuf> {
+        let handle_hash = self.handle_hash;
+        let mut selected = None;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::FileBrowserNavigated(components)
+                        if jailed_path(self.root, &components)
+                            .is_some_and(|path| path.is_dir()) =>
+                    {
+                        self.state.directory = components
+                    }
+                    EventKind::FileBrowserSelected(components) => {
+                        match jailed_path(self.root, &components) {
+                            Some(path) if path.is_file() && self.allowed(&path) => {
+                                self.state.selected = Some(components);
+                                selected = Some(path);
+                            }
+                            _ => warn!(
+                                "file browser {:?} can't select {:?}",
+                                handle_hash, components
+                            ),
+                        }
+                    }
+                    _ => warn!("wrong event for file browser {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        // The directory might have been removed in the meantime
+        let directory = match jailed_path(self.root, &self.state.directory) {
+            Some(directory) => directory,
+            None => {
+                self.state.directory.clear();
+                self.root.to_path_buf()
+            }
+        };
+        let (entries, error) = match self.entries(&directory) {
+            Ok(entries) => (entries, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::FileBrowser {
+                directory: self.state.directory.clone(),
+                entries,
+                selected: self.state.selected.clone(),
+                error,
+                attributes,
+            },
+        );
+        selected
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TerminalBuilder
+// ----------------------------------------------------------------------------
+
+/// Output of a `TerminalOutput` with an optional input line below it.
+pub struct TerminalBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    output: &'value TerminalOutput,
+    prompt: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for TerminalBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> TerminalBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        output: &'value TerminalOutput,
+    ) -> Self {
+        TerminalBuilder {
+            parent,
+            handle_hash,
+            output,
+            prompt: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Shows an input line after `prompt`, e.g. `"> "`. Earlier inputs can be recalled with the
+    /// arrow keys.
+    pub fn prompt<S: Into<String>>(mut self, prompt: S) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    /// Returns the lines the user entered since the last frame.
+    pub fn finish(self) -> Vec<String> {
+        let handle_hash = self.handle_hash;
+        let mut submitted = Vec::new();
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::TerminalSubmitted(line) if self.prompt.is_some() => {
+                        submitted.push(line)
+                    }
+                    _ => warn!("wrong event for terminal {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Terminal {
+                first_line: self.output.first_line(),
+                lines: self.output.lines(),
+                prompt: self.prompt,
+                attributes,
+            },
+        );
+        submitted
+    }
+}
+
+// ----------------------------------------------------------------------------
+// LogViewBuilder
+// ----------------------------------------------------------------------------
+
+/// Lines of a log. Only the lines that were added since the last frame are sent, so the log can
+/// grow without resending it.
+pub struct LogViewBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    lines: &'value [String],
+    follow: bool,
+    max_lines: Option<usize>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for LogViewBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent, 'value> LogViewBuilder<'parent, 'value> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        lines: &'value [String],
+    ) -> Self {
+        LogViewBuilder {
+            parent,
+            handle_hash,
+            lines,
+            follow: false,
+            max_lines: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Scrolls to new lines as long as the user didn't scroll up.
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Only shows the last `max_lines` lines.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    pub fn finish(self) {
+        let skipped = match self.max_lines {
+            Some(max_lines) => self.lines.len().saturating_sub(max_lines),
+            None => 0,
+        };
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::LogView {
+                lines: self.lines[skipped..].to_vec(),
+                follow: self.follow,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// CommandPaletteBuilder
+// ----------------------------------------------------------------------------
+
+/// Overlay with a searchable list of commands that is opened with a hotkey.
+pub struct CommandPaletteBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    hotkey: String,
+    commands: Vec<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for CommandPaletteBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> CommandPaletteBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        CommandPaletteBuilder {
+            parent,
+            handle_hash,
+            hotkey: String::from("Ctrl+K"),
+            commands: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Key combination that opens the palette, e.g. `"Ctrl+Shift+P"`. Defaults to `"Ctrl+K"`.
+    pub fn hotkey<S: Into<String>>(mut self, hotkey: S) -> Self {
+        self.hotkey = hotkey.into();
+        self
+    }
+
+    pub fn command<S: Into<String>>(mut self, name: S) -> Self {
+        self.commands.push(name.into());
+        self
+    }
+
+    pub fn commands<I: IntoIterator<Item = S>, S: Into<String>>(mut self, names: I) -> Self {
+        self.commands.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns the command the user picked since the last frame.
+    pub fn finish(self) -> Option<String> {
+        let handle_hash = self.handle_hash;
+        let mut invoked = None;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::CommandInvoked(name) if self.commands.contains(&name) => {
+                        invoked = Some(name)
+                    }
+                    _ => warn!(
+                        "wrong event for command palette {:?}: {:?}",
+                        handle_hash, kind
+                    ),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::CommandPalette {
+                hotkey: self.hotkey,
+                commands: self.commands,
+                attributes,
+            },
+        );
+        invoked
+    }
+}
+
+// ----------------------------------------------------------------------------
+// LiveImageBuilder
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    fn mime(self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Webp => "image/webp",
+        }
+    }
+}
+
+/// Compressed image that is sent to a live image as binary message next to the JSON update.
+#[derive(Debug)]
+pub(crate) struct Frame {
+    handle_hash: HandleHash,
+    format: ImageFormat,
+    bytes: Vec<u8>,
+}
+
+impl Frame {
+    /// Layout of the binary message: handle hash (u32, little endian), format (u8), image
+    pub(crate) fn to_message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(5 + self.bytes.len());
+        message.extend_from_slice(&self.handle_hash.0.to_le_bytes());
+        message.push(self.format as u8);
+        message.extend_from_slice(&self.bytes);
+        message
+    }
+
+    /// Inverse of `to_message`
+    #[cfg(feature = "client")]
+    pub(crate) fn from_message(message: &[u8]) -> Option<(HandleHash, ImageFormat, &[u8])> {
+        if message.len() < 5 {
+            return None;
+        }
+        let handle_hash = u32::from_le_bytes([message[0], message[1], message[2], message[3]]);
+        let format = match message[4] {
+            0 => ImageFormat::Jpeg,
+            1 => ImageFormat::Png,
+            2 => ImageFormat::Webp,
+            _ => return None,
+        };
+        Some((HandleHash(handle_hash), format, &message[5..]))
+    }
+}
+
+pub struct LiveImageBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    frame: Option<(ImageFormat, Vec<u8>)>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for LiveImageBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> LiveImageBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        LiveImageBuilder {
+            parent,
+            handle_hash,
+            frame: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Replaces the displayed image. Frames bypass the diff, so only pass a frame when it changed.
+    pub fn frame(mut self, format: ImageFormat, bytes: Vec<u8>) -> Self {
+        self.frame = Some((format, bytes));
+        self
+    }
+
+    pub fn finish(self) {
+        let handle_hash = self.handle_hash;
+        if let Some((format, bytes)) = self.frame {
+            self.parent.gui().borrow_mut().frames.push(Frame {
+                handle_hash,
+                format,
+                bytes,
+            });
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent
+            .push_element(handle_hash, Element::LiveImage { attributes });
+    }
+}
+
+// ----------------------------------------------------------------------------
+// LinePlotBuilder
+// ----------------------------------------------------------------------------
+
+pub struct LinePlotBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    series: Vec<PlotSeries>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for LinePlotBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> LinePlotBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        LinePlotBuilder {
+            parent,
+            handle_hash,
+            series: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Adds a line through the `points` in the order they are given. When points are only
+    /// appended, or dropped from the front like in a sliding window, the browser only receives
+    /// the new points.
+    pub fn series<S: Into<String>>(mut self, label: S, points: &[(f64, f64)]) -> Self {
+        self.series.push(PlotSeries {
+            label: label.into(),
+            points: points.to_vec(),
+        });
+        self
+    }
+
+    pub fn finish(self) {
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::LinePlot {
+                series: self.series,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// BarChartBuilder
+// ----------------------------------------------------------------------------
+
+pub struct BarChartBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    bars: Vec<Bar>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for BarChartBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> BarChartBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        BarChartBuilder {
+            parent,
+            handle_hash,
+            bars: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn bar<S: Into<String>>(mut self, label: S, value: f64) -> Self {
+        self.bars.push(Bar {
+            label: label.into(),
+            value,
+        });
+        self
+    }
+
+    /// Adds a bar for each of `bins` ranges of equal width between the smallest and the largest
+    /// of the `values`, which counts the values in the range. Values that aren't finite are left
+    /// out.
+    pub fn histogram(mut self, values: &[f64], bins: usize) -> Self {
+        let bins = bins.max(1);
+        let finite = || values.iter().copied().filter(|value| value.is_finite());
+        let min = finite().fold(f64::INFINITY, f64::min);
+        let max = finite().fold(f64::NEG_INFINITY, f64::max);
+        if min > max {
+            return self;
+        }
+        // The width underflows for values that are very close to each other
+        let width = if max > min {
+            ((max - min) / bins as f64).max(f64::MIN_POSITIVE)
+        } else {
+            1.0
+        };
+        let mut counts = vec![0usize; bins];
+        for value in finite() {
+            let bin = ((value - min) / width) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+        // Enough decimals to tell the ranges apart, within reason
+        let decimals = ((1.0 - width.log10().floor()).max(0.0) as usize).min(10);
+        for (bin, count) in counts.into_iter().enumerate() {
+            let start = min + bin as f64 * width;
+            self.bars.push(Bar {
+                label: format!("{:.*}–{:.*}", decimals, start, decimals, start + width),
+                value: count as f64,
+            });
+        }
+        self
+    }
+
+    pub fn finish(self) {
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::BarChart {
+                bars: self.bars,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// CanvasBuilder
+// ----------------------------------------------------------------------------
+
+pub struct CanvasBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    size: (u32, u32),
+    commands: Vec<DrawCommand>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for CanvasBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> CanvasBuilder<'parent> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        size: (u32, u32),
+    ) -> Self {
+        CanvasBuilder {
+            parent,
+            handle_hash,
+            size,
+            commands: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn command(mut self, command: DrawCommand) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn line<S: Into<String>>(
+        self,
+        from: (f64, f64),
+        to: (f64, f64),
+        color: S,
+        width: f64,
+    ) -> Self {
+        self.command(DrawCommand::Line {
+            from,
+            to,
+            color: color.into(),
+            width,
+        })
+    }
+
+    pub fn rect<S: Into<String>>(
+        self,
+        position: (f64, f64),
+        size: (f64, f64),
+        color: S,
+        filled: bool,
+    ) -> Self {
+        self.command(DrawCommand::Rect {
+            position,
+            size,
+            color: color.into(),
+            filled,
+        })
+    }
+
+    pub fn circle<S: Into<String>>(
+        self,
+        center: (f64, f64),
+        radius: f64,
+        color: S,
+        filled: bool,
+    ) -> Self {
+        self.command(DrawCommand::Circle {
+            center,
+            radius,
+            color: color.into(),
+            filled,
+        })
+    }
+
+    /// Text whose top left corner is at `position`
+    pub fn text<T: Into<String>, S: Into<String>>(
+        self,
+        position: (f64, f64),
+        text: T,
+        color: S,
+    ) -> Self {
+        self.command(DrawCommand::Text {
+            position,
+            text: text.into(),
+            color: color.into(),
+        })
+    }
+
+    /// Returns the position of a click in canvas pixels.
+    pub fn finish(self) -> Option<(f64, f64)> {
+        let handle_hash = self.handle_hash;
+        let mut clicked = None;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::CanvasClicked { x, y } => clicked = Some((x, y)),
+                    kind => warn!("wrong event for canvas {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Canvas {
+                width: self.size.0,
+                height: self.size.1,
+                commands: self.commands,
+                attributes,
+            },
+        );
+        clicked
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ImageBuilder
+// ----------------------------------------------------------------------------
+
+pub struct ImageBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    src: String,
+    alt: Option<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for ImageBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> ImageBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        ImageBuilder {
+            parent,
+            handle_hash,
+            src: String::new(),
+            alt: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Image the browser loads from `url`.
+    pub fn url<S: Into<String>>(mut self, url: S) -> Self {
+        self.src = url.into();
+        self
+    }
+
+    /// Image embedded into the GUI, e.g. a generated plot. The bytes are part of the element and
+    /// only sent again when they change. Use a live image for images that change often.
+    pub fn bytes(mut self, format: ImageFormat, bytes: &[u8]) -> Self {
+        self.src = format!(
+            "data:{};base64,{}",
+            format.mime(),
+            BASE64_STANDARD.encode(bytes)
+        );
+        self
+    }
+
+    /// Text shown when the image can't be displayed
+    pub fn alt<S: Into<String>>(mut self, alt: S) -> Self {
+        self.alt = Some(alt.into());
+        self
+    }
+
+    pub fn finish(self) {
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent.push_element(
+            self.handle_hash,
+            Element::Image {
+                src: self.src,
+                alt: self.alt,
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// WebComponentBuilder
+// ----------------------------------------------------------------------------
+
+pub struct WebComponentBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    tag: String,
+    dom_attributes: BTreeMap<String, String>,
+    properties: BTreeMap<String, serde_json::Value>,
+    events: Vec<String>,
+    modifiers: Modifiers<'parent>,
+}
+
+impl<'parent> Modify<'parent> for WebComponentBuilder<'parent> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+impl<'parent> WebComponentBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash, tag: String) -> Self {
+        WebComponentBuilder {
+            parent,
+            handle_hash,
+            tag,
+            dom_attributes: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            events: Vec::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Sets an HTML attribute of the custom element.
+    pub fn attribute<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.dom_attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets a JavaScript property of the custom element, which can hold structured data.
+    pub fn property<N: Into<String>, V: Into<serde_json::Value>>(
+        mut self,
+        name: N,
+        value: V,
+    ) -> Self {
+        self.properties.insert(name.into(), value.into());
+        self
+    }
+
+    /// Forwards the DOM event `name` of the custom element to the server.
+    pub fn on<N: Into<String>>(mut self, name: N) -> Self {
+        self.events.push(name.into());
+        self
+    }
+
+    /// Returns the subscribed DOM events that happened since the last frame.
+    pub fn finish(self) -> Vec<DomEvent> {
+        let handle_hash = self.handle_hash;
+        let mut dom_events = Vec::new();
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::DomEvent(dom_event) => dom_events.push(dom_event),
+                    _ => warn!(
+                        "wrong event for web component {:?}: {:?}",
+                        handle_hash, kind
+                    ),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::WebComponent {
+                tag: self.tag,
+                dom_attributes: self.dom_attributes,
+                properties: self.properties,
+                events: self.events,
+                attributes,
+            },
+        );
+        dom_events
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ChartBuilder
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "chartjs")]
+pub struct ChartBuilder<'parent, 'value> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    kind: ChartKind,
+    datasets: Vec<Dataset>,
+    decimation: Option<Decimation>,
+    x_range: Option<&'value mut Option<(f64, f64)>>,
+    hovered: Option<&'value mut Option<ChartPoint>>,
+    modifiers: Modifiers<'parent>,
+}
+
+#[cfg(feature = "chartjs")]
+impl<'parent> Modify<'parent> for ChartBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+#[cfg(feature = "chartjs")]
+impl<'parent, 'value> ChartBuilder<'parent, 'value> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash, kind: ChartKind) -> Self {
+        ChartBuilder {
+            parent,
+            handle_hash,
+            kind,
+            datasets: Vec::new(),
+            decimation: None,
+            x_range: None,
+            hovered: None,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    pub fn dataset<S: Into<String>>(mut self, label: S, points: Vec<(f64, f64)>) -> Self {
+        self.datasets.push(Dataset {
+            label: label.into(),
+            points,
+        });
+        self
+    }
+
+    pub fn decimation(mut self, decimation: Decimation) -> Self {
+        self.decimation = Some(decimation);
+        self
+    }
+
+    /// Lets the user zoom with the mouse wheel and pan by dragging. `x_range` is the visible
+    /// range of the x axis, `None` for all data, and can be changed by the server as well, e.g.
+    /// to load more detailed data for it. A double click shows all data again. Only applies to
+    /// line and scatter charts.
+    pub fn zoom(mut self, x_range: &'value mut Option<(f64, f64)>) -> Self {
+        self.x_range = Some(x_range);
+        self
+    }
+
+    /// Draws a crosshair at the pointer. `point` receives the data point nearest to it.
+    pub fn hover(mut self, point: &'value mut Option<ChartPoint>) -> Self {
+        self.hovered = Some(point);
+        self
+    }
+
+    pub fn finish(mut self) {
+        let handle_hash = self.handle_hash;
+        if let Some(kinds) = self.parent.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match (
+                    kind,
+                    self.x_range.as_deref_mut(),
+                    self.hovered.as_deref_mut(),
+                ) {
+                    (EventKind::ChartRangeChanged(range), Some(x_range), _) => *x_range = range,
+                    (EventKind::ChartHovered(point), _, Some(hovered)) => *hovered = point,
+                    (kind, _, _) => warn!("wrong event for chart {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        let attributes = self.modifiers.finish(self.parent.gui(), handle_hash);
+        self.parent.push_element(
+            handle_hash,
+            Element::Chart {
+                kind: self.kind,
+                datasets: self.datasets,
+                decimation: self.decimation,
+                x_range: self.x_range.as_deref().copied().flatten(),
+                zoomable: self.x_range.is_some(),
+                hover: self.hovered.is_some(),
+                attributes,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// PlotBuilder
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "plotters")]
+pub struct PlotBuilder<'parent, F> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    size: (u32, u32),
+    draw: F,
+    modifiers: Modifiers<'parent>,
+}
+
+#[cfg(feature = "plotters")]
+impl<'parent, F> Modify<'parent> for PlotBuilder<'parent, F> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+#[cfg(feature = "plotters")]
+impl<'parent, F> PlotBuilder<'parent, F>
+where
+    F: FnOnce(SVGBackend<'_>) -> Result<(), Box<dyn Error>>,
+{
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        size: (u32, u32),
+        draw: F,
+    ) -> Self {
+        PlotBuilder {
+            parent,
+            handle_hash,
+            size,
+            draw,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Renders the plot into an SVG. The browser only receives it again when the drawing changed.
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        let mut svg = String::new();
+        (self.draw)(SVGBackend::with_string(&mut svg, self.size))?;
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent
+            .push_element(self.handle_hash, Element::Svg { svg, attributes });
+        Ok(())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// FormBuilder
+// ----------------------------------------------------------------------------
+
+/// Rows of labels and inputs whose labels line up in one column.
+pub struct FormBuilder<'parent> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    fields: Vec<FormField>,
+}
+
+impl<'parent> FormBuilder<'parent> {
+    fn new(parent: &'parent mut dyn PushElement, handle_hash: HandleHash) -> Self {
+        FormBuilder {
+            parent,
+            handle_hash,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Row with the `label` in front of the elements that `build` adds.
+    pub fn field<S, F>(mut self, label: S, build: F) -> Self
+    where
+        S: Into<String>,
+        F: FnOnce(&mut StackLayout<'_>),
+    {
+        let content = HandleHash::combine(
+            self.handle_hash,
+            HandleHash::from_str(format!("field{}", self.fields.len())),
+        );
+        let state = self.parent.gui();
+        state.borrow_mut().elements.insert(
+            content,
+            Element::StackLayout {
+                children: Vec::new(),
+                align: Align::default(),
+                justify: Justify::default(),
+                style: None,
+            },
+        );
+        build(&mut StackLayout { state, id: content });
+        self.fields.push(FormField {
+            label: label.into(),
+            content,
+        });
+        self
+    }
+
+    pub fn finish(self) {
+        self.parent.push_element(
+            self.handle_hash,
+            Element::Form {
+                fields: self.fields,
+            },
+        );
+    }
+}
+
+// ----------------------------------------------------------------------------
+// QrCodeBuilder
+// ----------------------------------------------------------------------------
+
+#[cfg(feature = "qrcode")]
+pub struct QrCodeBuilder<'parent, 'data> {
+    parent: &'parent mut dyn PushElement,
+    handle_hash: HandleHash,
+    data: &'data [u8],
+    size: u32,
+    modifiers: Modifiers<'parent>,
+}
+
+#[cfg(feature = "qrcode")]
+impl<'parent> Modify<'parent> for QrCodeBuilder<'parent, '_> {
+    fn modifiers(&mut self) -> &mut Modifiers<'parent> {
+        &mut self.modifiers
+    }
+}
+
+#[cfg(feature = "qrcode")]
+impl<'parent, 'data> QrCodeBuilder<'parent, 'data> {
+    fn new(
+        parent: &'parent mut dyn PushElement,
+        handle_hash: HandleHash,
+        data: &'data [u8],
+    ) -> Self {
+        QrCodeBuilder {
+            parent,
+            handle_hash,
+            data,
+            size: 200,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[track_caller]
+    pub fn handle<H: Handle>(mut self, handle: &H) -> Self {
+        self.handle_hash = manual_handle(Location::caller(), handle);
+        self
+    }
+
+    /// Minimal width and height in pixels, 200 by default.
+    pub fn size(mut self, size: u32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Fails when the data doesn't fit into a QR code.
+    pub fn finish(self) -> Result<(), qrcode::types::QrError> {
+        let svg = qrcode::QrCode::new(self.data)?
+            .render::<qrcode::render::svg::Color<'_>>()
+            .min_dimensions(self.size, self.size)
+            .build();
+        let attributes = self.modifiers.finish(self.parent.gui(), self.handle_hash);
+        self.parent
+            .push_element(self.handle_hash, Element::Svg { svg, attributes });
+        Ok(())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Modifiers
+// ----------------------------------------------------------------------------
+
+/// State of the modifiers that are shared by all element builders.
+#[derive(Debug, Default)]
+pub struct Modifiers<'m> {
+    scroll_into_view: bool,
+    measure: Option<&'m mut Option<Geometry>>,
+    context_menu: Option<&'m mut Option<usize>>,
+    anchor: Option<&'m mut Option<HandleHash>>,
+    attributes: Attributes,
+}
+
+impl Modifiers<'_> {
+    fn finish(self, state: &RefCell<GuiState>, handle_hash: HandleHash) -> Attributes {
+        let mut state = state.borrow_mut();
+        if self.scroll_into_view {
+            state.scroll_to = Some(handle_hash);
+        }
+        if let Some(geometry) = self.measure {
+            *geometry = state.geometries.get(&handle_hash).copied();
+        }
+        if let Some(anchor) = self.anchor {
+            *anchor = Some(handle_hash);
+        }
+        if let Some(selected) = self.context_menu {
+            let items = self.attributes.context_menu.len();
+            *selected = state
+                .context_menu_selections
+                .remove(&handle_hash)
+                .filter(|index| *index < items);
+        }
+        self.attributes
+    }
+}
+
+pub trait Modify<'m>: Sized {
+    #[doc(hidden)]
+    fn modifiers(&mut self) -> &mut Modifiers<'m>;
+
+    /// Smoothly scrolls the element into view once the frame is shown.
+    fn scroll_into_view(mut self) -> Self {
+        self.modifiers().scroll_into_view = true;
+        self
+    }
+
+    /// Lets the browser report the rendered geometry of the element. `geometry` receives the
+    /// last reported value, which is `None` until the element was rendered.
+    fn measure(mut self, geometry: &'m mut Option<Geometry>) -> Self {
+        self.modifiers().attributes.measure = true;
+        self.modifiers().measure = Some(geometry);
+        self
+    }
+
+    /// Shows a menu with the `items` on a right click. `selected` receives the index of the item
+    /// that was clicked since the last frame and is `None` otherwise.
+    fn context_menu<I, S>(mut self, items: I, selected: &'m mut Option<usize>) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.modifiers().attributes.context_menu = items.into_iter().map(Into::into).collect();
+        self.modifiers().context_menu = Some(selected);
+        self
+    }
+
+    /// Writes the handle hash of the element to `anchor`, to place an overlay next to it with
+    /// `Gui::anchored_overlay`.
+    fn anchor(mut self, anchor: &'m mut Option<HandleHash>) -> Self {
+        self.modifiers().anchor = Some(anchor);
+        self
+    }
+
+    /// Text the browser shows when the pointer rests on the element (see the HTML `title`).
+    fn tooltip<S: Into<String>>(mut self, text: S) -> Self {
+        self.modifiers().attributes.tooltip = Some(text.into());
+        self
+    }
+
+    /// Adds the CSS class `name` to the element, so a user stylesheet can target it. Can be
+    /// called more than once.
+    fn class<S: Into<String>>(mut self, name: S) -> Self {
+        self.modifiers().attributes.classes.push(name.into());
+        self
+    }
+
+    /// Hides the element without removing it from the page, so it keeps state like the scroll
+    /// position or the focus for when it is shown again. Visible by default.
+    fn visible(mut self, visible: bool) -> Self {
+        self.modifiers().attributes.hidden = !visible;
+        self
+    }
+
+    /// Changes the look of just this element, e.g. to highlight a value.
+    fn style(mut self, style: Style) -> Self {
+        self.modifiers().attributes.style = Some(style);
+        self
+    }
+}
+
+/// Modifiers for elements the user can interact with.
+pub trait Interactive<'m>: Modify<'m> {
+    /// Position of the element in the keyboard traversal order (see the HTML `tabindex`).
+    fn tab_index(mut self, index: i32) -> Self {
+        self.modifiers().attributes.tab_index = Some(index);
+        self
+    }
+}
+
+impl<'parent> Interactive<'parent> for TextboxBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for ButtonBuilder<'parent> {}
+impl<'parent> Interactive<'parent> for CheckboxBuilder<'parent, '_> {}
+#[cfg(feature = "number")]
+impl<'parent, T> Interactive<'parent> for NumberBuilder<'parent, '_, T> {}
+impl<'parent> Interactive<'parent> for SliderBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for ColorBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for FileUploadBuilder<'parent> {}
+#[cfg(feature = "chrono")]
+impl<'parent> Interactive<'parent> for DateBuilder<'parent, '_> {}
+#[cfg(feature = "chrono")]
+impl<'parent> Interactive<'parent> for TimeBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for ListBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for ReorderableListBuilder<'parent> {}
+impl<'parent> Interactive<'parent> for DropdownBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for TreeBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for TimelineBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for CalendarBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for FileBrowserBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for TerminalBuilder<'parent, '_> {}
+impl<'parent> Interactive<'parent> for CanvasBuilder<'parent> {}
+
+// ----------------------------------------------------------------------------
+// traits
+// ----------------------------------------------------------------------------
+
+pub struct CurveBall<'p> {
+    push_element: &'p mut dyn PushElement,
+}
+
+trait PushElement {
+    fn push_element(&mut self, id: HandleHash, element: Element);
+    fn gui(&self) -> &RefCell<GuiState>;
+}
+
+pub trait Elements {
+    #[doc(hidden)]
+    fn curve_ball(&mut self) -> CurveBall<'_>;
+
+    #[track_caller]
+    fn header<S: Into<String>>(&mut self, text: S) {
+        let e = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        e.push_element(id, Element::Header(text.into()))
+    }
+
+    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
+    #[track_caller]
+    fn label<T: AsRef<str>>(&mut self, text: T) -> LabelBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        LabelBuilder::new(parent, id, text.as_ref().to_string())
+    }
+
+    /// Label composed of spans with their own formatting. Unlike `label` the text is never
+    /// interpreted as HTML.
+    #[must_use = "The finish method has to be called on the RichTextBuilder to create a rich text."]
+    #[track_caller]
+    fn rich_text(&mut self) -> RichTextBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        RichTextBuilder::new(parent, id)
+    }
+
+    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
+    #[track_caller]
+    fn text_box<'s>(&mut self, text: &'s mut String) -> TextboxBuilder<'_, 's> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TextboxBuilder::new(parent, id, text)
+    }
+
+    /// Text box for search terms that debounces the keystrokes in the browser.
+    #[must_use = "The finish method has to be called on the SearchBoxBuilder to create a search box."]
+    #[track_caller]
+    fn search_box<'s>(&mut self, text: &'s mut String) -> SearchBoxBuilder<'_, 's> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        SearchBoxBuilder::new(parent, id, text)
+    }
+
+    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
+    #[track_caller]
+    fn button(&mut self) -> ButtonBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        ButtonBuilder::new(parent, id)
+    }
+
+    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
+    #[track_caller]
+    fn checkbox<'value>(&mut self, value: &'value mut bool) -> CheckboxBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        CheckboxBuilder::new(parent, id, value)
+    }
+
+    #[cfg(feature = "number")]
+    #[must_use = "The finish method has to be called on the ButtonBuilder to create a button."]
+    #[track_caller]
+    fn number<'value, T>(&mut self, value: &'value mut T) -> NumberBuilder<'_, 'value, T>
+    where
+        T: Copy + NumCast + ToPrimitive + Bounded,
     {
         let parent = self.curve_ball().push_element;
         let id = HandleHash::from_caller();
         NumberBuilder::new(parent, id, value)
     }
 
+    /// Range input for floating-point values like tuning parameters.
+    #[must_use = "The finish method has to be called on the SliderBuilder to create a slider."]
+    #[track_caller]
+    fn slider<'value>(&mut self, value: &'value mut f64) -> SliderBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        SliderBuilder::new(parent, id, value)
+    }
+
+    /// Color picker for a color with red, green and blue.
+    #[must_use = "The finish method has to be called on the ColorBuilder to create a color picker."]
+    #[track_caller]
+    fn color<'value>(&mut self, rgb: &'value mut [u8; 3]) -> ColorBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        ColorBuilder::new(parent, id, rgb)
+    }
+
+    #[must_use = "The finish method has to be called on the FileUploadBuilder to create a file upload."]
+    #[track_caller]
+    fn file_upload(&mut self) -> FileUploadBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        FileUploadBuilder::new(parent, id)
+    }
+
+    #[cfg(feature = "chrono")]
+    #[must_use = "The finish method has to be called on the DateBuilder to create a date picker."]
+    #[track_caller]
+    fn date<'value>(&mut self, date: &'value mut NaiveDate) -> DateBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        DateBuilder::new(parent, id, date)
+    }
+
+    #[cfg(feature = "chrono")]
+    #[must_use = "The finish method has to be called on the TimeBuilder to create a time picker."]
+    #[track_caller]
+    fn time<'value>(&mut self, time: &'value mut NaiveTime) -> TimeBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TimeBuilder::new(parent, id, time)
+    }
+
+    /// List of items of which one can be selected with pointer or keyboard.
+    #[must_use = "The finish method has to be called on the ListBuilder to create a list."]
+    #[track_caller]
+    fn list<'value>(&mut self, selected: &'value mut Option<usize>) -> ListBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        ListBuilder::new(parent, id, selected)
+    }
+
+    /// List of items that are put into another order by drag and drop or with Alt and the arrow
+    /// keys.
+    #[must_use = "The finish method has to be called on the ReorderableListBuilder to create a reorderable list."]
+    #[track_caller]
+    fn reorderable_list(&mut self) -> ReorderableListBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        ReorderableListBuilder::new(parent, id)
+    }
+
+    /// Select box of `option`s of which `selected` is the index.
+    #[must_use = "The finish method has to be called on the DropdownBuilder to create a dropdown."]
+    #[track_caller]
+    fn dropdown<'value>(&mut self, selected: &'value mut usize) -> DropdownBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        DropdownBuilder::new(parent, id, selected)
+    }
+
+    /// Tree of items of which one can be selected with pointer or keyboard.
+    #[must_use = "The finish method has to be called on the TreeBuilder to create a tree."]
+    #[track_caller]
+    fn tree<'value>(
+        &mut self,
+        selected: &'value mut Option<Vec<usize>>,
+    ) -> TreeBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TreeBuilder::new(parent, id, selected)
+    }
+
+    /// Bars of `items` on a shared time axis, e.g. for schedules or traces.
+    #[must_use = "The finish method has to be called on the TimelineBuilder to create a timeline."]
+    #[track_caller]
+    fn timeline<'value>(
+        &mut self,
+        items: &'value mut [TimelineItem],
+    ) -> TimelineBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TimelineBuilder::new(parent, id, items)
+    }
+
+    /// Month grid of the days of `month`, which the user can switch to another month.
+    #[must_use = "The finish method has to be called on the CalendarBuilder to create a calendar."]
+    #[track_caller]
+    fn calendar<'value>(
+        &mut self,
+        month: &'value mut CalendarMonth,
+    ) -> CalendarBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        CalendarBuilder::new(parent, id, month)
+    }
+
+    /// Table of text cells. Columns and rows are added with `TableBuilder::column` and
+    /// `TableBuilder::row`. Sorting, filtering and selecting rows is opt-in.
+    #[must_use = "The finish method has to be called on the TableBuilder to create a table."]
+    #[track_caller]
+    fn table<'value>(&mut self) -> TableBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TableBuilder::new(parent, id)
+    }
+
+    /// Text with a copy to clipboard button, e.g. for tokens or commands.
+    #[must_use = "The finish method has to be called on the CopyableBuilder to create a copyable."]
+    #[track_caller]
+    fn copyable<S: Into<String>>(&mut self, text: S) -> CopyableBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        CopyableBuilder::new(parent, id, text.into())
+    }
+
+    /// Chips of `tags` that can be removed and a text box that adds new ones on Enter.
+    #[must_use = "The finish method has to be called on the TagsBuilder to create tags."]
+    #[track_caller]
+    fn tags<'value>(&mut self, tags: &'value mut Vec<String>) -> TagsBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TagsBuilder::new(parent, id, tags)
+    }
+
+    /// Path of segments from the root to the current location, e.g. of a directory. The last
+    /// segment is the current location.
+    #[must_use = "The finish method has to be called on the BreadcrumbsBuilder to create breadcrumbs."]
+    #[track_caller]
+    fn breadcrumbs<S: AsRef<str>>(&mut self, segments: &[S]) -> BreadcrumbsBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        let segments = segments.iter().map(|s| s.as_ref().to_owned()).collect();
+        BreadcrumbsBuilder::new(parent, id, segments)
+    }
+
+    /// Page buttons with previous and next arrows. `current` is the index of the shown page and
+    /// is kept below `total_pages`.
+    #[must_use = "The finish method has to be called on the PaginationBuilder to create a pagination."]
+    #[track_caller]
+    fn pagination<'value>(
+        &mut self,
+        current: &'value mut usize,
+        total_pages: usize,
+    ) -> PaginationBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        PaginationBuilder::new(parent, id, current, total_pages)
+    }
+
+    /// Differences between the lines of `old` and `new`.
+    #[must_use = "The finish method has to be called on the DiffViewBuilder to create a diff view."]
+    #[track_caller]
+    fn diff_view<'value>(
+        &mut self,
+        old: &'value str,
+        new: &'value str,
+    ) -> DiffViewBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        DiffViewBuilder::new(parent, id, old, new)
+    }
+
+    /// Structured data like a payload or a configuration as a collapsible tree.
+    #[must_use = "The finish method has to be called on the JsonViewBuilder to create a JSON view."]
+    #[track_caller]
+    fn json_view(&mut self, value: serde_json::Value) -> JsonViewBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        JsonViewBuilder::new(parent, id, value)
+    }
+
+    /// Lets the user pick a file below `root`. `state` keeps the directory and the selection.
+    #[must_use = "The finish method has to be called on the FileBrowserBuilder to create a file browser."]
+    #[track_caller]
+    fn file_browser<'value>(
+        &mut self,
+        root: &'value Path,
+        state: &'value mut FileBrowserState,
+    ) -> FileBrowserBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        FileBrowserBuilder::new(parent, id, root, state)
+    }
+
+    /// Streamed output like the one of a process, optionally with an input line.
+    #[must_use = "The finish method has to be called on the TerminalBuilder to create a terminal."]
+    #[track_caller]
+    fn terminal<'value>(&mut self, output: &'value TerminalOutput) -> TerminalBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        TerminalBuilder::new(parent, id, output)
+    }
+
+    /// Scrollable log of `lines` that only renders the lines in view, for long logs.
+    #[must_use = "The finish method has to be called on the LogViewBuilder to create a log view."]
+    #[track_caller]
+    fn log_view<'value>(&mut self, lines: &'value [String]) -> LogViewBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        LogViewBuilder::new(parent, id, lines)
+    }
+
+    /// Overlay that is opened with a hotkey and lets the user search `command`s by name.
+    #[must_use = "The finish method has to be called on the CommandPaletteBuilder to create a command palette."]
+    #[track_caller]
+    fn command_palette(&mut self) -> CommandPaletteBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        CommandPaletteBuilder::new(parent, id)
+    }
+
+    /// Line plot that is drawn by the browser, e.g. for streamed measurements. Unlike `plot` of
+    /// the `plotters` feature it only sends the points that were added.
+    #[must_use = "The finish method has to be called on the LinePlotBuilder to create a line plot."]
+    #[track_caller]
+    fn line_plot(&mut self) -> LinePlotBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        LinePlotBuilder::new(parent, id)
+    }
+
+    /// Bar chart that is drawn by the browser, e.g. of a histogram.
+    #[must_use = "The finish method has to be called on the BarChartBuilder to create a bar chart."]
+    #[track_caller]
+    fn bar_chart(&mut self) -> BarChartBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        BarChartBuilder::new(parent, id)
+    }
+
+    /// Canvas of `width` x `height` pixels that the browser draws the commands of the builder on.
+    #[must_use = "The finish method has to be called on the CanvasBuilder to create a canvas."]
+    #[track_caller]
+    fn canvas(&mut self, width: u32, height: u32) -> CanvasBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        CanvasBuilder::new(parent, id, (width, height))
+    }
+
+    /// Image from a URL or from embedded bytes
+    #[must_use = "The finish method has to be called on the ImageBuilder to create an image."]
+    #[track_caller]
+    fn image(&mut self) -> ImageBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        ImageBuilder::new(parent, id)
+    }
+
+    #[must_use = "The finish method has to be called on the LiveImageBuilder to create a live image."]
+    #[track_caller]
+    fn live_image(&mut self) -> LiveImageBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        LiveImageBuilder::new(parent, id)
+    }
+
+    /// Embeds the custom element `tag` (e.g. `"my-widget"`), which has to be defined in the page.
+    #[must_use = "The finish method has to be called on the WebComponentBuilder to create a web component."]
+    #[track_caller]
+    fn web_component<S: Into<String>>(&mut self, tag: S) -> WebComponentBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        WebComponentBuilder::new(parent, id, tag.into())
+    }
+
+    /// Chart rendered by Chart.js in the browser. Changed datasets are updated in place.
+    #[cfg(feature = "chartjs")]
+    #[must_use = "The finish method has to be called on the ChartBuilder to create a chart."]
+    #[track_caller]
+    fn chart<'value>(&mut self, kind: ChartKind) -> ChartBuilder<'_, 'value> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        ChartBuilder::new(parent, id, kind)
+    }
+
+    /// Draws a chart with the `plotters` crate, e.g. by calling `into_drawing_area` on the backend.
+    #[cfg(feature = "plotters")]
+    #[must_use = "The finish method has to be called on the PlotBuilder to create a plot."]
+    #[track_caller]
+    fn plot<F>(&mut self, width: u32, height: u32, draw: F) -> PlotBuilder<'_, F>
+    where
+        F: FnOnce(SVGBackend<'_>) -> Result<(), Box<dyn Error>>,
+    {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        PlotBuilder::new(parent, id, (width, height), draw)
+    }
+
+    /// QR code of `data`, e.g. of the URL of the page so that a phone can open it.
+    #[cfg(feature = "qrcode")]
+    #[must_use = "The finish method has to be called on the QrCodeBuilder to create a QR code."]
+    #[track_caller]
+    fn qr_code<'data, D: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        data: &'data D,
+    ) -> QrCodeBuilder<'_, 'data> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::from_caller();
+        QrCodeBuilder::new(parent, id, data.as_ref())
+    }
+
+    /// Bordered box with a `title` around related elements.
+    #[track_caller]
+    fn group<'gui, S: Into<String>>(&'gui mut self, title: S) -> Indeterminate<'gui> {
+        let e = self.curve_ball().push_element;
+        let handle_hash = HandleHash::combine(
+            HandleHash::from_caller(),
+            HandleHash::from_str(e.gui().borrow_mut().fetch_id().to_string()),
+        );
+        let content = HandleHash::combine(handle_hash, HandleHash::from_str("content"));
+        e.gui()
+            .borrow_mut()
+            .elements
+            .insert(content, Element::Indeterminate);
+        e.push_element(
+            handle_hash,
+            Element::Group {
+                title: title.into(),
+                content,
+            },
+        );
+        Indeterminate::new(e.gui(), content)
+    }
+
+    /// Rows of labeled inputs, e.g. for a settings page.
+    #[must_use = "The finish method has to be called on the FormBuilder to create a form."]
+    #[track_caller]
+    fn form(&mut self) -> FormBuilder<'_> {
+        let parent = self.curve_ball().push_element;
+        let id = HandleHash::combine(
+            HandleHash::from_caller(),
+            HandleHash::from_str(parent.gui().borrow_mut().fetch_id().to_string()),
+        );
+        FormBuilder::new(parent, id)
+    }
+
     #[track_caller]
     fn layout<'gui>(&'gui mut self) -> Indeterminate<'gui> {
         let e = self.curve_ball().push_element;
@@ -637,78 +5065,75 @@ pub trait Elements {
         e.push_element(handle_hash, Element::Indeterminate);
         Indeterminate::new(e.gui(), handle_hash)
     }
+
+    /// Section with a `title` that is opened and closed by a click on the title. The elements of
+    /// the returned layout can be left out while the section is closed.
+    #[track_caller]
+    fn collapsible<'gui, S: Into<String>>(
+        &'gui mut self,
+        title: S,
+        open: &mut bool,
+    ) -> StackLayout<'gui> {
+        let e = self.curve_ball().push_element;
+        let handle_hash = HandleHash::combine(
+            HandleHash::from_caller(),
+            HandleHash::from_str(e.gui().borrow_mut().fetch_id().to_string()),
+        );
+        if let Some(kinds) = e.gui().borrow_mut().events.remove(&handle_hash) {
+            for kind in kinds {
+                match kind {
+                    EventKind::CollapsibleToggled(toggled) => *open = toggled,
+                    kind => warn!("wrong event for collapsible {:?}: {:?}", handle_hash, kind),
+                }
+            }
+        }
+        e.push_element(
+            handle_hash,
+            Element::Collapsible {
+                title: title.into(),
+                open: *open,
+                children: Vec::new(),
+            },
+        );
+        StackLayout {
+            state: e.gui(),
+            id: handle_hash,
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
 // Element
 // ----------------------------------------------------------------------------
 
-#[derive(Debug, PartialEq, Eq, Serialize, Clone)]
-enum Element {
-    Indeterminate,
-    Header(String),
-    Label(String),
-    Textbox(String),
-    Button {
-        text: Option<String>,
-    },
-    Checkbox {
-        text: Option<String>,
-        checked: bool,
-    },
-    Number {
-        text: Option<String>,
-        min: Option<i32>,
-        max: Option<i32>,
-        step: Option<i32>,
-        value: i32,
-    },
-    StackLayout {
-        children: Vec<HandleHash>,
-    },
-    Columns {
-        left: HandleHash,
-        right: HandleHash,
-    },
-}
-
 impl Element {
-    fn new_button<T: Into<Option<String>>>(text: T) -> Element {
-        Element::Button { text: text.into() }
+    fn new_button<T: Into<Option<String>>>(
+        text: T,
+        icon: Option<Icon>,
+        call_js: Option<String>,
+        shortcut: Option<String>,
+        gestures: bool,
+        attributes: Attributes,
+    ) -> Element {
+        Element::Button {
+            text: text.into(),
+            icon,
+            call_js,
+            shortcut,
+            gestures,
+            attributes,
+        }
     }
 
-    fn new_checkbox<T: Into<Option<String>>>(text: T, checked: bool) -> Element {
-        Element::Checkbox { text: text.into(), checked }
+    fn new_checkbox<T: Into<Option<String>>>(
+        text: T,
+        checked: bool,
+        attributes: Attributes,
+    ) -> Element {
+        Element::Checkbox {
+            text: text.into(),
+            checked,
+            attributes,
+        }
     }
 }
-
-// ----------------------------------------------------------------------------
-//
-// ----------------------------------------------------------------------------
-
-#[derive(Debug, Deserialize, Clone)]
-pub enum EventKind {
-    ButtonPressed,
-    CheckboxChecked(bool),
-    NumberChanged(i32),
-    TextboxChanged(String),
-}
-
-#[derive(Debug, Deserialize, Clone)]
-pub struct Event {
-    pub handle_hash: HandleHash,
-    pub kind: EventKind,
-}
-
-/// Json value
-#[derive(Debug, Serialize)]
-#[serde(transparent)]
-struct JsonString(String);
-
-#[derive(Debug, Serialize)]
-pub struct ServerBrowserUpdate {
-    root: Option<HandleHash>,
-    added: BTreeMap<HandleHash, Element>, // key must be String for serde_json
-    removed: Vec<HandleHash>,
-    updated: BTreeMap<HandleHash, Element>, // key must be String for serde_json
-}
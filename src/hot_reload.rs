@@ -0,0 +1,44 @@
+use log::info;
+use std::{
+    fs, io,
+    path::Path,
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::{connection::Shared, protocol::ServerBrowserMessage};
+
+const WEB_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/web");
+
+/// The page is read from disk on every request so that changes show up without recompiling.
+/// Fails e.g. for a moment while an editor replaces the file.
+pub(crate) fn page() -> io::Result<String> {
+    fs::read_to_string(Path::new(WEB_DIRECTORY).join("index.html"))
+}
+
+fn last_modified() -> Option<SystemTime> {
+    fs::read_dir(WEB_DIRECTORY)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Tells all connected browsers to reload the page when a file in `web` changed.
+pub(crate) fn watch(shared: Arc<Shared>) {
+    thread::spawn(move || {
+        let mut modified = last_modified();
+        loop {
+            thread::sleep(Duration::from_millis(500));
+            let current = last_modified();
+            if current == modified {
+                continue;
+            }
+            modified = current;
+            info!("Web assets changed, reloading the browsers");
+            for connection in shared.connections.lock().iter_mut() {
+                connection.send(&ServerBrowserMessage::Reload);
+            }
+        }
+    });
+}
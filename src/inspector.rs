@@ -0,0 +1,92 @@
+use log::warn;
+use std::{collections::BTreeMap, io::Write, net::TcpStream};
+use uuid::Uuid;
+
+use crate::connection::{write_http_response, Shared};
+
+/// Answers the requests below `/_protocol`.
+pub(crate) fn respond(
+    stream: &mut TcpStream,
+    shared: &Shared,
+    path: &str,
+    query: &BTreeMap<String, String>,
+) -> std::io::Result<()> {
+    if !authorized(stream, shared, query.get("token")) {
+        warn!(
+            "Refused access to the inspector from {:?}",
+            stream.peer_addr()
+        );
+        return write_http_response(stream, "403 Forbidden", "text/plain", "");
+    }
+    match path {
+        "/_protocol" => page(stream),
+        "/_protocol/connections" => connections(stream, shared),
+        "/_protocol/stream" => self::stream(stream, shared, query.get("uuid")),
+        _ => write_http_response(stream, "404 Not Found", "text/plain", ""),
+    }
+}
+
+/// With a token from `ServerBuilder::inspector_token` the request has to carry it, otherwise
+/// it has to come from the local host.
+fn authorized(stream: &TcpStream, shared: &Shared, token: Option<&String>) -> bool {
+    match (&shared.inspector_token, token) {
+        (Some(expected), Some(token)) => constant_time_eq(expected.as_bytes(), token.as_bytes()),
+        (Some(_), None) => false,
+        (None, _) => stream
+            .peer_addr()
+            .is_ok_and(|address| address.ip().is_loopback()),
+    }
+}
+
+/// Compares without returning early, so the time taken doesn't tell how much of the token was
+/// guessed right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Page that shows the messages of a connection as they are exchanged.
+fn page(stream: &mut TcpStream) -> std::io::Result<()> {
+    let page = include_str!("../web/protocol.html");
+    write_http_response(stream, "200 OK", "text/html", page)
+}
+
+fn connections(stream: &mut TcpStream, shared: &Shared) -> std::io::Result<()> {
+    let uuids = shared
+        .connections
+        .lock()
+        .iter()
+        .map(|connection| connection.uuid().to_string())
+        .collect::<Vec<_>>();
+    let body = serde_json::to_string(&uuids).unwrap();
+    write_http_response(stream, "200 OK", "application/json", &body)
+}
+
+/// Streams the messages of the connection `uuid` as server-sent events until either side
+/// disconnects.
+fn stream(stream: &mut TcpStream, shared: &Shared, uuid: Option<&String>) -> std::io::Result<()> {
+    let uuid = uuid.and_then(|uuid| Uuid::parse_str(uuid).ok());
+    let tap = shared
+        .connections
+        .lock()
+        .iter_mut()
+        .find(|connection| Some(connection.uuid()) == uuid)
+        .map(|connection| connection.tap());
+    let tap = match tap {
+        Some(tap) => tap,
+        None => return write_http_response(stream, "404 Not Found", "text/plain", ""),
+    };
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n",
+    )?;
+    stream.flush()?;
+    for message in tap {
+        let data = serde_json::to_string(&message).unwrap();
+        stream.write_all(format!("data: {}\n\n", data).as_bytes())?;
+        stream.flush()?;
+    }
+    Ok(())
+}
@@ -1,5 +1,31 @@
+mod backplane;
+#[cfg(feature = "client")]
+mod client;
 mod connection;
+#[cfg(feature = "desktop")]
+mod desktop;
 mod gui;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+#[cfg(feature = "inspector")]
+mod inspector;
+#[cfg(feature = "mdns")]
+mod mdns;
+mod outbox;
+mod polling;
+pub mod protocol;
+#[cfg(feature = "webrtc")]
+mod rtc;
+mod session;
+mod terminal;
+mod theme;
+mod workers;
 
+pub use backplane::*;
+#[cfg(feature = "client")]
+pub use client::*;
 pub use connection::*;
-pub use gui::*;
\ No newline at end of file
+pub use gui::*;
+pub use protocol::{Event, EventKind, ServerBrowserUpdate};
+pub use terminal::*;
+pub use theme::*;
@@ -0,0 +1,29 @@
+use mdns_sd::{Error, ServiceDaemon, ServiceInfo};
+use std::collections::HashMap;
+
+use crate::Server;
+
+impl Server {
+    /// Announces the page as `_http._tcp` service called `name` on the local network so that
+    /// tablets and phones find it without typing the address. The `Server` has to listen on an
+    /// address that is reachable from the network, e.g. `0.0.0.0:8080`.
+    pub fn announce(&mut self, name: &str) -> Result<(), Error> {
+        let daemon = ServiceDaemon::new()?;
+        let host_name = format!(
+            "{}.local.",
+            name.replace(|c: char| !c.is_alphanumeric(), "-")
+        );
+        let service = ServiceInfo::new(
+            "_http._tcp.local.",
+            name,
+            &host_name,
+            (),
+            self.address().port(),
+            HashMap::new(),
+        )?
+        .enable_addr_auto();
+        daemon.register(service)?;
+        self.mdns = Some(daemon);
+        Ok(())
+    }
+}
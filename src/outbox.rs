@@ -0,0 +1,85 @@
+use parking_lot::{Condvar, Mutex};
+use std::{collections::VecDeque, io, sync::Arc, thread};
+
+use crate::{Transport, TransportMessage};
+
+/// Messages that may wait for a browser before it counts as too slow.
+const OUTBOX_CAPACITY: usize = 64;
+
+#[derive(Default)]
+struct Queue {
+    messages: VecDeque<TransportMessage>,
+    closed: bool,
+    error: Option<io::Error>,
+}
+
+#[derive(Default)]
+struct OutboxShared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+pub(crate) enum OutboxError {
+    /// The browser didn't take the queued messages yet
+    Full,
+    Transport(io::Error),
+}
+
+/// Bounded queue of messages for the browser. They are written by a thread per connection so that
+/// a stalled browser doesn't block the thread calling `Connection::show_gui`.
+pub(crate) struct Outbox(Arc<OutboxShared>);
+
+impl Outbox {
+    pub(crate) fn new(mut transport: Box<dyn Transport>) -> Self {
+        let shared = Arc::new(OutboxShared::default());
+        let writer = shared.clone();
+        thread::spawn(move || loop {
+            let message = {
+                let mut queue = writer.queue.lock();
+                loop {
+                    if queue.closed {
+                        return;
+                    }
+                    if let Some(message) = queue.messages.pop_front() {
+                        break message;
+                    }
+                    writer.changed.wait(&mut queue);
+                }
+            };
+            if let Err(err) = transport.send(message) {
+                let mut queue = writer.queue.lock();
+                queue.messages.clear();
+                queue.closed = true;
+                queue.error = Some(err);
+                return;
+            }
+        });
+        Self(shared)
+    }
+
+    /// Queues `message` or reports the error of an earlier write.
+    pub(crate) fn push(&self, message: TransportMessage) -> Result<(), OutboxError> {
+        let mut queue = self.0.queue.lock();
+        if let Some(err) = queue.error.take() {
+            return Err(OutboxError::Transport(err));
+        }
+        if queue.messages.len() >= OUTBOX_CAPACITY {
+            return Err(OutboxError::Full);
+        }
+        queue.messages.push_back(message);
+        self.0.changed.notify_one();
+        Ok(())
+    }
+
+    /// Drops the messages that were not written yet.
+    pub(crate) fn clear(&self) {
+        self.0.queue.lock().messages.clear();
+    }
+}
+
+impl Drop for Outbox {
+    fn drop(&mut self) {
+        self.0.queue.lock().closed = true;
+        self.0.changed.notify_one();
+    }
+}
@@ -0,0 +1,185 @@
+use log::warn;
+use parking_lot::{Condvar, Mutex};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{connection::Shared, Socket, Transport, TransportMessage};
+
+/// A poll is answered with an empty batch after this time so that proxies don't cut it off.
+const POLL_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Channels that were not requested for this time are considered closed.
+const CHANNEL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Messages the browser didn't acknowledge yet. Once there are this many, sending blocks until
+/// the browser polls, so the outbox of the connection fills up and its `SlowClientPolicy` applies.
+const QUEUE_CAPACITY: usize = 64;
+
+#[derive(Clone, Serialize)]
+enum PolledMessage {
+    Text(String),
+    Binary(String), // Hex encoded
+}
+
+#[derive(Serialize)]
+struct PollBatch {
+    /// Sequence number the browser has to ask for next.
+    seq: u64,
+    messages: Vec<PolledMessage>,
+}
+
+#[derive(Default)]
+struct Queue {
+    next_seq: u64,
+    messages: VecDeque<(u64, PolledMessage)>,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct PollQueue {
+    queue: Mutex<Queue>,
+    arrived: Condvar,
+    /// Notified when messages were acknowledged or the channel was closed
+    polled: Condvar,
+}
+
+struct PollingTransport(Arc<PollQueue>);
+
+impl Transport for PollingTransport {
+    fn send(&mut self, message: TransportMessage) -> io::Result<()> {
+        let deadline = Instant::now() + CHANNEL_TIMEOUT;
+        let mut queue = self.0.queue.lock();
+        while queue.messages.len() >= QUEUE_CAPACITY && !queue.closed {
+            if self.0.polled.wait_until(&mut queue, deadline).timed_out() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+        }
+        if queue.closed {
+            return Err(io::ErrorKind::ConnectionAborted.into());
+        }
+        let message = match message {
+            TransportMessage::Text(text) => PolledMessage::Text(text),
+            TransportMessage::Binary(data) => {
+                PolledMessage::Binary(data.iter().map(|b| format!("{:02x}", b)).collect())
+            }
+        };
+        let seq = queue.next_seq;
+        queue.next_seq += 1;
+        queue.messages.push_back((seq, message));
+        self.0.arrived.notify_all();
+        Ok(())
+    }
+}
+
+struct PollChannel {
+    socket: Socket,
+    queue: Arc<PollQueue>,
+    last_seen: Instant,
+}
+
+/// Long-polling channels as last resort when neither websockets nor WebRTC get through. Every
+/// channel stands in for one websocket and is identified by an id chosen by the browser.
+#[derive(Default)]
+pub(crate) struct Polling {
+    channels: Mutex<BTreeMap<String, PollChannel>>,
+}
+
+impl Polling {
+    fn remove_timed_out(channels: &mut BTreeMap<String, PollChannel>) {
+        channels.retain(|_, channel| {
+            let alive = channel.last_seen.elapsed() < CHANNEL_TIMEOUT;
+            if !alive {
+                channel.queue.queue.lock().closed = true;
+                channel.queue.polled.notify_all();
+            }
+            alive
+        });
+    }
+
+    /// Calls `f` with channel `id`, which is opened if the browser didn't use it before.
+    fn with_channel<R>(shared: &Arc<Shared>, id: &str, f: impl FnOnce(&mut PollChannel) -> R) -> R {
+        let mut channels = shared.polling.channels.lock();
+        Self::remove_timed_out(&mut channels);
+        let channel = channels.entry(id.to_owned()).or_insert_with(|| {
+            let queue = Arc::new(PollQueue::default());
+            let transport = Box::new(PollingTransport(queue.clone()));
+            PollChannel {
+                socket: Socket::new(shared.clone(), transport),
+                queue,
+                last_seen: Instant::now(),
+            }
+        });
+        channel.last_seen = Instant::now();
+        f(channel)
+    }
+
+    /// Like `with_channel` but only for channels the browser already posted a message on.
+    fn with_open_channel<R>(
+        shared: &Arc<Shared>,
+        id: &str,
+        f: impl FnOnce(&mut PollChannel) -> R,
+    ) -> Option<R> {
+        let mut channels = shared.polling.channels.lock();
+        Self::remove_timed_out(&mut channels);
+        let channel = channels.get_mut(id)?;
+        channel.last_seen = Instant::now();
+        Some(f(channel))
+    }
+
+    /// Handles a message the browser posted on channel `id`.
+    pub(crate) fn receive(shared: &Arc<Shared>, id: &str, message: &str) {
+        Self::with_channel(shared, id, |channel| channel.socket.receive(message));
+    }
+
+    /// Handles a hex encoded binary message the browser posted on channel `id`.
+    pub(crate) fn receive_binary(shared: &Arc<Shared>, id: &str, message: &str) {
+        let data = (0..message.len() / 2)
+            .map(|i| {
+                message
+                    .get(2 * i..2 * i + 2)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>();
+        match data {
+            Some(data) => {
+                Self::with_channel(shared, id, |channel| channel.socket.receive_binary(&data))
+            }
+            None => warn!("Could not decode binary message on channel {}", id),
+        }
+    }
+
+    /// Waits until there are messages with a sequence number of at least `seq` and returns them
+    /// as a batch. Messages before `seq` were received by the browser and are dropped. Returns
+    /// `None` for channels that were not opened by posting a message.
+    pub(crate) fn poll(shared: &Arc<Shared>, id: &str, seq: u64) -> Option<String> {
+        let poll_queue = Self::with_open_channel(shared, id, |channel| channel.queue.clone())?;
+        let deadline = Instant::now() + POLL_TIMEOUT;
+        let mut queue = poll_queue.queue.lock();
+        while queue.messages.front().is_some_and(|(s, _)| *s < seq) {
+            queue.messages.pop_front();
+        }
+        poll_queue.polled.notify_all();
+        while queue.messages.is_empty() && !queue.closed {
+            if poll_queue
+                .arrived
+                .wait_until(&mut queue, deadline)
+                .timed_out()
+            {
+                break;
+            }
+        }
+        let batch = PollBatch {
+            seq: queue.next_seq,
+            messages: queue.messages.iter().map(|(_, m)| m.clone()).collect(),
+        };
+        drop(queue);
+        // Waiting doesn't count as inactivity
+        Self::with_open_channel(shared, id, |_| {});
+        Some(serde_json::to_string(&batch).unwrap())
+    }
+}
@@ -0,0 +1,1565 @@
+#[cfg(feature = "schema")]
+use schemars::{
+    gen::SchemaSettings,
+    schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec, SubschemaValidation},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, panic::Location};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum WebsocketDirection {
+    ToBrowser,
+    ToServer,
+}
+
+/// Every text message the server sends to the browser.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ServerBrowserMessage {
+    Update(ServerBrowserUpdate),
+    ClearUiState,
+    Print,
+    PrintStylesheet(String),
+    EvalJs {
+        id: u64,
+        code: String,
+    },
+    RegisterJsFunction {
+        name: String,
+        code: String,
+    },
+    Speak {
+        text: String,
+        options: SpeakOptions,
+    },
+    SetIdleTimeout(Option<u64>),
+    SetDebugOverlay(bool),
+    /// Sent when the page changed on disk in development (feature `hot-reload`)
+    Reload,
+    Prompt {
+        id: u64,
+        text: String,
+        default: String,
+    },
+    /// Sent before a message of the browser is ignored because it broke the protocol, e.g. an
+    /// event before the `Welcome`
+    ProtocolError(String),
+    /// Announces a file for the browser to download. Its content follows in a binary message:
+    /// `id` (u32, little endian), `DOWNLOAD_FRAME_FORMAT` (u8), content
+    Download {
+        id: u32,
+        name: String,
+        mime: String,
+    },
+}
+
+/// Takes the place of the image format in binary messages that carry a download.
+pub const DOWNLOAD_FRAME_FORMAT: u8 = 255;
+
+/// Part of the page URL the browser reports when it connects.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BrowserLocation {
+    pub query: BTreeMap<String, String>,
+    pub fragment: String,
+}
+
+/// Every text message the browser sends to the server. The first message on each websocket is
+/// a `Welcome` that decides its direction.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum BrowserServerMessage {
+    Welcome {
+        direction: WebsocketDirection,
+        uuid: String,
+        #[serde(default)]
+        location: BrowserLocation,
+    },
+    Event(Event),
+    FragmentChanged(String),
+    JsResult {
+        id: u64,
+        result: Result<serde_json::Value, String>,
+    },
+    JsFunctionReturned {
+        name: String,
+        result: Result<serde_json::Value, String>,
+    },
+    IdleChanged(bool),
+    PromptAnswered {
+        id: PromptHandle,
+        value: Option<String>,
+    },
+    /// Announces a file whose content follows in binary messages, each the `id` (u32, little
+    /// endian) followed by a chunk of the file.
+    UploadStarted {
+        handle_hash: HandleHash,
+        id: u32,
+        name: String,
+        size: u64,
+    },
+}
+
+/// Node of the GUI as it is sent to the browser. Children are referenced by their `HandleHash`.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Element {
+    Indeterminate,
+    Header(String),
+    Label {
+        text: String,
+        icon: Option<Icon>,
+        style: LabelStyle,
+        attributes: Attributes,
+    },
+    RichText {
+        spans: Vec<TextSpan>,
+        attributes: Attributes,
+    },
+    Textbox {
+        text: String,
+        /// Message of a failed validation
+        error: Option<String>,
+        /// Whether focus changes are sent
+        report_focus: bool,
+        /// Whether Enter sends `EventKind::TextboxSubmitted`
+        submit_on_enter: bool,
+        attributes: Attributes,
+    },
+    SearchBox {
+        text: String,
+        placeholder: Option<String>,
+        debounce_ms: u64,
+        attributes: Attributes,
+    },
+    Button {
+        text: Option<String>,
+        icon: Option<Icon>,
+        call_js: Option<String>,
+        /// Keys that press the button anywhere on the page, e.g. `"Ctrl+Enter"`
+        shortcut: Option<String>,
+        /// Whether double clicks and long presses are reported
+        gestures: bool,
+        attributes: Attributes,
+    },
+    Checkbox {
+        text: Option<String>,
+        checked: bool,
+        attributes: Attributes,
+    },
+    Number {
+        text: Option<String>,
+        min: Option<f64>,
+        max: Option<f64>,
+        step: Option<f64>,
+        value: f64,
+        /// Whether only whole numbers are valid, e.g. for `i32` but not for `f32`
+        integer: bool,
+        /// Whether the value is sent while it changes instead of once it was entered
+        live: bool,
+        /// Message of a failed validation
+        error: Option<String>,
+        /// Whether focus changes are sent
+        report_focus: bool,
+        attributes: Attributes,
+    },
+    Slider {
+        text: Option<String>,
+        min: f64,
+        max: f64,
+        step: Option<f64>,
+        value: f64,
+        /// Whether the value is sent while the slider is dragged instead of when it is released
+        live: bool,
+        attributes: Attributes,
+    },
+    List {
+        items: Vec<String>,
+        selected: Option<usize>,
+        /// Whether double clicks and long presses of the items are reported
+        gestures: bool,
+        attributes: Attributes,
+    },
+    ReorderableList {
+        items: Vec<String>,
+        attributes: Attributes,
+    },
+    Color {
+        text: Option<String>,
+        rgb: [u8; 3],
+        attributes: Attributes,
+    },
+    /// Dates are formatted like `2024-12-31`, as chrono's `NaiveDate` does
+    Date {
+        text: Option<String>,
+        min: Option<String>,
+        max: Option<String>,
+        date: String,
+        attributes: Attributes,
+    },
+    /// `time` is formatted like `23:59:30`, as chrono's `NaiveTime` does
+    Time {
+        text: Option<String>,
+        seconds: bool,
+        time: String,
+        attributes: Attributes,
+    },
+    FileUpload {
+        text: Option<String>,
+        accept: Option<String>,
+        multiple: bool,
+        attributes: Attributes,
+    },
+    Dropdown {
+        text: Option<String>,
+        options: Vec<String>,
+        selected: usize,
+        attributes: Attributes,
+    },
+    Tree {
+        items: Vec<TreeItem>,
+        selected: Option<Vec<usize>>,
+        attributes: Attributes,
+    },
+    LiveImage {
+        attributes: Attributes,
+    },
+    /// `src` is a URL or a data URL of embedded bytes
+    Image {
+        src: String,
+        alt: Option<String>,
+        attributes: Attributes,
+    },
+    WebComponent {
+        tag: String,
+        dom_attributes: BTreeMap<String, String>,
+        properties: BTreeMap<String, serde_json::Value>,
+        events: Vec<String>,
+        attributes: Attributes,
+    },
+    Chart {
+        kind: ChartKind,
+        datasets: Vec<Dataset>,
+        decimation: Option<Decimation>,
+        /// Visible range of the x axis, `None` shows all data
+        #[serde(default)]
+        x_range: Option<(f64, f64)>,
+        #[serde(default)]
+        zoomable: bool,
+        #[serde(default)]
+        hover: bool,
+        attributes: Attributes,
+    },
+    #[cfg(any(feature = "plotters", feature = "qrcode"))]
+    Svg {
+        svg: String,
+        attributes: Attributes,
+    },
+    Timeline {
+        items: Vec<TimelineItem>,
+        editable: bool,
+        snap: Option<f64>,
+        attributes: Attributes,
+    },
+    Calendar {
+        month: CalendarMonth,
+        /// Badges by day of the month
+        days: BTreeMap<u32, Vec<String>>,
+        attributes: Attributes,
+    },
+    Table {
+        columns: Vec<String>,
+        /// Input of each column, `None` for columns that are not editable
+        editors: Vec<Option<CellEditor>>,
+        rows: Vec<Vec<String>>,
+        sortable: bool,
+        sort: Option<TableSort>,
+        /// Text of the filter box, `None` without one
+        filter: Option<String>,
+        selectable: bool,
+        selected: Option<usize>,
+        attributes: Attributes,
+    },
+    Copyable {
+        text: String,
+        attributes: Attributes,
+    },
+    Tags {
+        tags: Vec<String>,
+        placeholder: Option<String>,
+        attributes: Attributes,
+    },
+    Breadcrumbs {
+        segments: Vec<String>,
+        attributes: Attributes,
+    },
+    Pagination {
+        current: usize,
+        total_pages: usize,
+        attributes: Attributes,
+    },
+    DiffView {
+        lines: Vec<DiffLine>,
+        unified: bool,
+        attributes: Attributes,
+    },
+    JsonView {
+        value: serde_json::Value,
+        /// Levels that are expanded by default
+        expanded: usize,
+        attributes: Attributes,
+    },
+    FileBrowser {
+        /// Path of the shown directory below the root
+        directory: Vec<String>,
+        entries: Vec<FileEntry>,
+        selected: Option<Vec<String>>,
+        /// Why the directory couldn't be listed
+        error: Option<String>,
+        attributes: Attributes,
+    },
+    Terminal {
+        /// Number of the first line since the output was created
+        first_line: u64,
+        lines: Vec<TerminalLine>,
+        /// Input line is shown when set
+        prompt: Option<String>,
+        attributes: Attributes,
+    },
+    CommandPalette {
+        hotkey: String,
+        commands: Vec<String>,
+        attributes: Attributes,
+    },
+    /// Reports the `keys` pressed anywhere on the page, every key if there are none
+    KeyListener {
+        keys: Vec<String>,
+    },
+    StackLayout {
+        children: Vec<HandleHash>,
+        align: Align,
+        justify: Justify,
+        style: Option<Style>,
+    },
+    /// Children in rows that wrap when they are full
+    WrapLayout {
+        children: Vec<HandleHash>,
+        align: Align,
+        justify: Justify,
+        style: Option<Style>,
+    },
+    Columns {
+        columns: Vec<HandleHash>,
+        widths: Vec<Width>,
+        /// Shows handles between the columns to drag their widths
+        resizable: bool,
+    },
+    Form {
+        fields: Vec<FormField>,
+    },
+    Group {
+        title: String,
+        content: HandleHash,
+    },
+    Collapsible {
+        title: String,
+        open: bool,
+        children: Vec<HandleHash>,
+    },
+    LinePlot {
+        series: Vec<PlotSeries>,
+        attributes: Attributes,
+    },
+    LogView {
+        lines: Vec<String>,
+        /// Scrolls to new lines unless the user scrolled up
+        follow: bool,
+        attributes: Attributes,
+    },
+    BarChart {
+        bars: Vec<Bar>,
+        attributes: Attributes,
+    },
+    Canvas {
+        width: u32,
+        height: u32,
+        commands: Vec<DrawCommand>,
+        attributes: Attributes,
+    },
+    /// Only the content of the selected tab is built
+    Tabs {
+        titles: Vec<String>,
+        selected: usize,
+        content: HandleHash,
+    },
+    /// Floats above the root, below the element `anchor` if there is one
+    Overlay {
+        anchor: Option<HandleHash>,
+        content: HandleHash,
+    },
+    /// Floats above the root and is moved by dragging its title
+    Window {
+        title: String,
+        /// Top left corner in CSS pixels, placed by the browser if there is none
+        position: Option<(f64, f64)>,
+        closable: bool,
+        content: HandleHash,
+    },
+    /// Pinned to the top of the window, the root is moved down by its height
+    TopBar {
+        content: HandleHash,
+    },
+    /// Pinned to the bottom of the window, the root ends above it
+    BottomBar {
+        content: HandleHash,
+    },
+    ScrollArea {
+        max_height: u32,
+        /// Scroll position of a tracked scroll area in pixels
+        scroll_top: Option<f64>,
+        content: HandleHash,
+    },
+}
+
+impl EventKind {
+    /// Whether the event only carries the latest value of an element, so that an earlier event
+    /// of the same kind that wasn't handled yet can be dropped.
+    pub(crate) fn replaces_earlier(&self) -> bool {
+        matches!(
+            self,
+            EventKind::NumberChanged(_) | EventKind::SliderChanged(_) | EventKind::Scrolled(_)
+        )
+    }
+}
+
+impl Element {
+    /// Elements that are placed inside of this one
+    pub(crate) fn children(&self) -> Vec<HandleHash> {
+        match self {
+            Element::StackLayout { children, .. }
+            | Element::WrapLayout { children, .. }
+            | Element::Collapsible { children, .. } => children.clone(),
+            Element::Columns { columns, .. } => columns.clone(),
+            Element::Tabs { content, .. }
+            | Element::ScrollArea { content, .. }
+            | Element::Overlay { content, .. }
+            | Element::Window { content, .. }
+            | Element::Group { content, .. }
+            | Element::TopBar { content }
+            | Element::BottomBar { content } => vec![*content],
+            Element::Form { fields } => fields.iter().map(|field| field.content).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Modifiers that end up in the `Element` and are applied to the DOM node by the browser.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Attributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_index: Option<i32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub measure: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+    /// Items of the menu shown on a right click
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_menu: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<Style>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// CSS classes added by the user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub classes: Vec<String>,
+}
+
+/// Row of an `Element::Form`
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FormField {
+    pub label: String,
+    pub content: HandleHash,
+}
+
+/// Line of an `Element::LinePlot`
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlotSeries {
+    pub label: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Bar of an `Element::BarChart`
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Bar {
+    pub label: String,
+    pub value: f64,
+}
+
+/// Drawing of an `Element::Canvas` in canvas pixels, replayed in order by the browser. Colors
+/// are CSS colors, e.g. `"red"` or `"#1e92fe"`.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DrawCommand {
+    Line {
+        from: (f64, f64),
+        to: (f64, f64),
+        color: String,
+        width: f64,
+    },
+    Rect {
+        position: (f64, f64),
+        size: (f64, f64),
+        color: String,
+        filled: bool,
+    },
+    Circle {
+        center: (f64, f64),
+        radius: f64,
+        color: String,
+        filled: bool,
+    },
+    Text {
+        position: (f64, f64),
+        text: String,
+        color: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Dataset {
+    pub label: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum EventKind {
+    ButtonPressed,
+    /// Of a button or the selected item of a list
+    DoubleClicked,
+    /// Of a button or the selected item of a list
+    LongPressed,
+    FocusGained,
+    FocusLost,
+    /// Text of a text box in which Enter was pressed
+    TextboxSubmitted(String),
+    KeyPressed {
+        key: String,
+        modifiers: KeyModifiers,
+    },
+    CheckboxChecked(bool),
+    NumberChanged(f64),
+    SliderChanged(f64),
+    ColorChanged([u8; 3]),
+    /// Assembled by the server from an upload, never sent by the browser as is
+    FileUploaded {
+        name: String,
+        bytes: Vec<u8>,
+    },
+    /// Formatted like `Element::Date`
+    DateChanged(String),
+    /// Formatted like `Element::Time`
+    TimeChanged(String),
+    TextboxChanged(String),
+    Measured(Geometry),
+    DomEvent(DomEvent),
+    ListSelected(usize),
+    /// `to` is the index of the item after it was moved
+    ItemMoved {
+        from: usize,
+        to: usize,
+    },
+    DropdownChanged(usize),
+    TreeSelected(Vec<usize>),
+    CommandInvoked(String),
+    TimelineClicked(usize),
+    CalendarDayClicked(u32),
+    CalendarMonthChanged(CalendarMonth),
+    FileBrowserNavigated(Vec<String>),
+    FileBrowserSelected(Vec<String>),
+    TerminalSubmitted(String),
+    TableSortRequested(usize),
+    TableFilterChanged(String),
+    TableRowClicked(usize),
+    TableCellEdited {
+        row: usize,
+        column: usize,
+        text: String,
+    },
+    TabSelected(usize),
+    /// Weights of all columns after the user dragged a handle
+    ColumnsResized(Vec<f64>),
+    /// Scroll position of a scroll area in pixels
+    Scrolled(f64),
+    PageSelected(usize),
+    /// Top left corner of a window in CSS pixels after the user dragged it
+    WindowMoved {
+        x: f64,
+        y: f64,
+    },
+    WindowClosed,
+    BreadcrumbClicked(usize),
+    Copied,
+    TagAdded(String),
+    TagRemoved(usize),
+    CollapsibleToggled(bool),
+    CanvasClicked {
+        x: f64,
+        y: f64,
+    },
+    /// Taken from the events of the element and handed to the modifiers
+    ContextMenuSelected(usize),
+    TimelineMoved {
+        index: usize,
+        start: f64,
+        end: f64,
+    },
+    ChartRangeChanged(Option<(f64, f64)>),
+    ChartHovered(Option<ChartPoint>),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Event {
+    pub handle_hash: HandleHash,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ServerBrowserUpdate {
+    pub root: Option<HandleHash>,
+    /// Elements rendered above the root in this order, `None` when they didn't change
+    #[serde(default)]
+    pub layers: Option<Vec<HandleHash>>,
+    /// Title of the page, `None` when it didn't change
+    #[serde(default)]
+    pub title: Option<String>,
+    /// CSS variables of the `Theme`, `None` when it didn't change
+    #[serde(default)]
+    pub theme: Option<BTreeMap<String, String>>,
+    pub added: BTreeMap<HandleHash, Element>, // key must be String for serde_json
+    pub removed: Vec<HandleHash>,
+    pub updated: BTreeMap<HandleHash, Element>, // key must be String for serde_json
+    /// Elements that changed but are sent without the part the browser already has
+    #[serde(default)]
+    pub appended: BTreeMap<HandleHash, Append>,
+    pub scroll_to: Option<HandleHash>,
+}
+
+/// Change of an element that mostly grew, e.g. by the lines of a terminal or the points of a
+/// line plot.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Append {
+    Terminal(TerminalAppend),
+    LinePlot(LinePlotAppend),
+    LogView(LogViewAppend),
+}
+
+impl Append {
+    /// Change from `previous` to `current` if the browser can keep a part of `previous`.
+    pub(crate) fn between(previous: &Element, current: &Element) -> Option<Append> {
+        TerminalAppend::between(previous, current)
+            .map(Append::Terminal)
+            .or_else(|| LinePlotAppend::between(previous, current).map(Append::LinePlot))
+            .or_else(|| LogViewAppend::between(previous, current).map(Append::LogView))
+    }
+
+    /// Applies the change to the element the browser has.
+    pub fn apply(&self, element: &mut Element) {
+        match self {
+            Append::Terminal(append) => append.apply(element),
+            Append::LinePlot(append) => append.apply(element),
+            Append::LogView(append) => append.apply(element),
+        }
+    }
+}
+
+/// Change of the lines of an `Element::Terminal`. The lines before `first_line` are dropped and
+/// the lines from `from_line` on are replaced by `lines`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TerminalAppend {
+    pub first_line: u64,
+    pub from_line: u64,
+    pub lines: Vec<TerminalLine>,
+}
+
+impl TerminalAppend {
+    /// Lines that changed from `previous` to `current` if only the lines of a terminal changed
+    /// and the browser can keep the ones it has.
+    pub(crate) fn between(previous: &Element, current: &Element) -> Option<TerminalAppend> {
+        match (previous, current) {
+            (
+                Element::Terminal {
+                    first_line: previous_first,
+                    lines: previous_lines,
+                    prompt: previous_prompt,
+                    attributes: previous_attributes,
+                },
+                Element::Terminal {
+                    first_line,
+                    lines,
+                    prompt,
+                    attributes,
+                },
+            ) if first_line >= previous_first
+                && prompt == previous_prompt
+                && attributes == previous_attributes =>
+            {
+                let skipped = (first_line - previous_first) as usize;
+                let unchanged = previous_lines
+                    .iter()
+                    .skip(skipped)
+                    .zip(lines)
+                    .take_while(|(previous, current)| previous == current)
+                    .count();
+                Some(TerminalAppend {
+                    first_line: *first_line,
+                    from_line: first_line + unchanged as u64,
+                    lines: lines[unchanged..].to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies the change to the `Element::Terminal` the browser has.
+    pub fn apply(&self, element: &mut Element) {
+        if let Element::Terminal {
+            first_line, lines, ..
+        } = element
+        {
+            // Lines are dropped first because the browser may not have all lines before
+            // `from_line`, e.g. when the last line was empty
+            let skipped = (self.first_line.saturating_sub(*first_line) as usize).min(lines.len());
+            lines.drain(..skipped);
+            lines.truncate(self.from_line.saturating_sub(self.first_line) as usize);
+            lines.extend(self.lines.iter().cloned());
+            *first_line = self.first_line;
+        }
+    }
+}
+
+/// Length of the longest end of `previous` that `current` starts with, i.e. the items a sliding
+/// window kept. Linear in the length of both with the prefix function of Knuth-Morris-Pratt.
+fn overlap<T: PartialEq>(previous: &[T], current: &[T]) -> usize {
+    // Length of the longest prefix of `current[..=i]` that is also a proper suffix of it
+    let mut prefix = vec![0; current.len()];
+    for i in 1..current.len() {
+        let mut k = prefix[i - 1];
+        while k > 0 && current[i] != current[k] {
+            k = prefix[k - 1];
+        }
+        if current[i] == current[k] {
+            k += 1;
+        }
+        prefix[i] = k;
+    }
+    let mut matched = 0;
+    for item in previous {
+        while matched > 0 && (matched == current.len() || *item != current[matched]) {
+            matched = prefix[matched - 1];
+        }
+        if matched < current.len() && *item == current[matched] {
+            matched += 1;
+        }
+    }
+    matched
+}
+
+/// Change of the points of an `Element::LinePlot`, one entry per series.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LinePlotAppend {
+    pub series: Vec<SeriesAppend>,
+}
+
+/// The first `dropped` points of a series are removed and `points` are appended.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SeriesAppend {
+    pub dropped: usize,
+    pub points: Vec<(f64, f64)>,
+}
+
+impl LinePlotAppend {
+    /// Points that changed from `previous` to `current` if only the points of a line plot changed.
+    /// Series that moved like a sliding window keep the points that are still shown.
+    pub(crate) fn between(previous: &Element, current: &Element) -> Option<LinePlotAppend> {
+        match (previous, current) {
+            (
+                Element::LinePlot {
+                    series: previous_series,
+                    attributes: previous_attributes,
+                },
+                Element::LinePlot { series, attributes },
+            ) if attributes == previous_attributes && series.len() == previous_series.len() => {
+                let series = previous_series
+                    .iter()
+                    .zip(series)
+                    .map(|(previous, current)| {
+                        if previous.label != current.label {
+                            return None;
+                        }
+                        let kept = overlap(&previous.points, &current.points);
+                        let dropped = previous.points.len() - kept;
+                        Some(SeriesAppend {
+                            dropped,
+                            points: current.points[kept..].to_vec(),
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(LinePlotAppend { series })
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies the change to the `Element::LinePlot` the browser has.
+    pub fn apply(&self, element: &mut Element) {
+        if let Element::LinePlot { series, .. } = element {
+            for (series, append) in series.iter_mut().zip(&self.series) {
+                let dropped = append.dropped.min(series.points.len());
+                series.points.drain(..dropped);
+                series.points.extend(append.points.iter().copied());
+            }
+        }
+    }
+}
+
+/// Change of the lines of an `Element::LogView`. The first `dropped` lines are removed and
+/// `lines` are appended.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LogViewAppend {
+    pub dropped: usize,
+    pub lines: Vec<String>,
+}
+
+impl LogViewAppend {
+    /// Lines that were added from `previous` to `current` if the lines that are still shown
+    /// didn't change, e.g. when the oldest lines were dropped because of `max_lines`.
+    pub(crate) fn between(previous: &Element, current: &Element) -> Option<LogViewAppend> {
+        match (previous, current) {
+            (
+                Element::LogView {
+                    lines: previous_lines,
+                    follow: previous_follow,
+                    attributes: previous_attributes,
+                },
+                Element::LogView {
+                    lines,
+                    follow,
+                    attributes,
+                },
+            ) if follow == previous_follow && attributes == previous_attributes => {
+                let kept = overlap(previous_lines, lines);
+                let dropped = previous_lines.len() - kept;
+                Some(LogViewAppend {
+                    dropped,
+                    lines: lines[kept..].to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies the change to the `Element::LogView` the browser has.
+    pub fn apply(&self, element: &mut Element) {
+        if let Element::LogView { lines, .. } = element {
+            let dropped = self.dropped.min(lines.len());
+            lines.drain(..dropped);
+            lines.extend(self.lines.iter().cloned());
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct HandleHash(pub(crate) u32);
+
+impl HandleHash {
+    pub(crate) fn from_location(location: &Location) -> Self {
+        let file = fxhash::hash32(location.file());
+        let line = fxhash::hash32(&location.line());
+        let column = fxhash::hash32(&location.column());
+        let hash = fxhash::hash32(&(file ^ line ^ column));
+        HandleHash(hash)
+    }
+
+    #[track_caller]
+    pub(crate) fn from_caller() -> Self {
+        Self::from_location(Location::caller())
+    }
+
+    pub(crate) fn from_str<S: AsRef<str>>(s: S) -> Self {
+        HandleHash(fxhash::hash32(s.as_ref()))
+    }
+
+    #[inline]
+    pub(crate) fn combine(h1: Self, h2: Self) -> HandleHash {
+        HandleHash(fxhash::hash32(&(h1.0 ^ h2.0)))
+    }
+}
+
+/// Modifier keys that were held down when a key was pressed.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct KeyModifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+/// Width of a column.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Width {
+    /// CSS pixels
+    Px(f64),
+    /// Percent of the width of the columns
+    Percent(f64),
+    /// Share of the width that is left by the other columns, relative to the other weights
+    Weight(f64),
+}
+
+/// Placement of the children across the layout, horizontal in a stack layout and vertical
+/// within the rows of a wrap layout.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    /// Children take the whole width, or height in a wrap layout
+    #[default]
+    Stretch,
+}
+
+/// Placement of the children along the layout, vertical in a stack layout that is higher than
+/// its children and horizontal within the rows of a wrap layout.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Justify {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// The free space is put between the children
+    SpaceBetween,
+}
+
+/// Formatting of the whole text of a label.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LabelStyle {
+    pub monospace: bool,
+    /// CSS color, e.g. `"red"` or `"#1e92fe"`
+    pub color: Option<String>,
+    /// Font size in CSS pixels
+    pub size: Option<f64>,
+    /// Whether long text continues on the next line instead of overflowing
+    pub wrap: bool,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        Self {
+            monospace: false,
+            color: None,
+            size: None,
+            wrap: true,
+        }
+    }
+}
+
+/// Text of a rich text with one formatting. Colors are CSS colors, e.g. `"red"` or `"#1e92fe"`.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TextSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub monospace: bool,
+    pub color: Option<String>,
+}
+
+impl TextSpan {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        TextSpan {
+            text: text.into(),
+            ..TextSpan::default()
+        }
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub fn monospace(mut self) -> Self {
+        self.monospace = true;
+        self
+    }
+
+    pub fn color<S: Into<String>>(mut self, color: S) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+/// Icon drawn by the browser in front of the text of a button or label.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Icon {
+    Add,
+    Remove,
+    Close,
+    Check,
+    Edit,
+    Trash,
+    Save,
+    Refresh,
+    Search,
+    Settings,
+    Play,
+    Pause,
+    Stop,
+    Download,
+    Upload,
+    Warning,
+    Info,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Copy,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TreeItem {
+    text: String,
+    children: Vec<TreeItem>,
+}
+
+impl TreeItem {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        TreeItem {
+            text: text.into(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn child(mut self, child: TreeItem) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn children(&self) -> &[TreeItem] {
+        &self.children
+    }
+}
+
+/// Bar of a timeline from `start` to `end`, e.g. seconds since the start of a trace. Items with
+/// the same `row` are shown on the same line.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TimelineItem {
+    pub row: String,
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TimelineItem {
+    pub fn new<R: Into<String>, T: Into<String>>(row: R, text: T, start: f64, end: f64) -> Self {
+        TimelineItem {
+            row: row.into(),
+            text: text.into(),
+            start,
+            end,
+        }
+    }
+}
+
+/// Month shown by a calendar. `month` goes from 1 to 12.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CalendarMonth {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl CalendarMonth {
+    pub fn new(year: i32, month: u32) -> Self {
+        CalendarMonth { year, month }
+    }
+
+    pub fn days(&self) -> u32 {
+        match self.month {
+            4 | 6 | 9 | 11 => 30,
+            2 if self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0) => 29,
+            2 => 28,
+            _ => 31,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self.month {
+            12 => CalendarMonth::new(self.year + 1, 1),
+            month => CalendarMonth::new(self.year, month + 1),
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self.month {
+            1 => CalendarMonth::new(self.year - 1, 12),
+            month => CalendarMonth::new(self.year, month - 1),
+        }
+    }
+}
+
+/// Column a table is sorted by, see `TableBuilder::sort`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TableSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// Input the cells of an editable column are shown with, see `TableBuilder::editable_column`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CellEditor {
+    Text,
+    Number,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DiffTag {
+    Equal,
+    Delete,
+    Insert,
+    /// Unchanged lines left out between two hunks
+    Skipped,
+}
+
+/// Line of a diff with its 1-based line numbers in the old and new text.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileEntry {
+    pub name: String,
+    pub directory: bool,
+    /// Size in bytes of a file
+    pub size: Option<u64>,
+}
+
+/// DOM event of a web component that was subscribed to with `WebComponentBuilder::on`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DomEvent {
+    pub name: String,
+    /// `detail` of the `CustomEvent` or `null`
+    pub detail: serde_json::Value,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ChartKind {
+    Line,
+    Bar,
+    Scatter,
+}
+
+/// Reduces the number of drawn points of large datasets in the browser (see the decimation
+/// plugin of Chart.js). Only applies to line charts.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Decimation {
+    /// Largest-Triangle-Three-Buckets keeping `samples` points
+    Lttb { samples: usize },
+    /// Keeps the minimum and maximum of every pixel column
+    MinMax,
+}
+
+/// Data point under the pointer of a chart with `ChartBuilder::hover`.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ChartPoint {
+    /// Index of the dataset in the order they were added
+    pub dataset: usize,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Rendered position and size of an element in the page in CSS pixels.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Geometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// One-off look of an element, rendered as inline styles. Fields that are `None` keep the look
+/// of the page, e.g. `Style { bold: true, ..Style::default() }`.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Style {
+    /// CSS color of the text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// CSS color of the background
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    /// Font size in CSS pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bold: bool,
+    /// Width in CSS pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    /// Height in CSS pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    /// CSS color of a border around the element
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<String>,
+    /// Radius of rounded corners in CSS pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub corner_radius: Option<f64>,
+    /// Distance between the border and the content in CSS pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<f64>,
+}
+
+/// Options for `Connection::speak` which map to the browser's `SpeechSynthesisUtterance`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SpeakOptions {
+    pub rate: f32,
+    pub pitch: f32,
+    pub volume: f32,
+    pub lang: Option<String>,
+    /// Stops what is currently spoken instead of queuing the text.
+    pub interrupt: bool,
+}
+
+impl Default for SpeakOptions {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            pitch: 1.0,
+            volume: 1.0,
+            lang: None,
+            interrupt: false,
+        }
+    }
+}
+
+/// Identifies a prompt opened with `Connection::prompt`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct PromptHandle(pub(crate) u64);
+
+/// Text of a terminal line with one style. Colors are indices into the 256 colors of xterm.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TerminalSpan {
+    pub text: String,
+    pub fg: Option<u8>,
+    pub bg: Option<u8>,
+    pub bold: bool,
+}
+
+pub type TerminalLine = Vec<TerminalSpan>;
+
+/// JSON Schema of the messages exchanged with the browser. Every text message sent by the
+/// server is a `ServerBrowserMessage` and every text message sent by the browser a
+/// `BrowserServerMessage`. `ServerBrowserUpdate`, `Element` and `EventKind` are part of the
+/// definitions as well.
+#[cfg(feature = "schema")]
+pub fn schema() -> RootSchema {
+    let mut generator = SchemaSettings::draft07().into_generator();
+    let messages = vec![
+        generator.subschema_for::<ServerBrowserMessage>(),
+        generator.subschema_for::<BrowserServerMessage>(),
+    ];
+    generator.subschema_for::<ServerBrowserUpdate>();
+    generator.subschema_for::<Element>();
+    generator.subschema_for::<EventKind>();
+    RootSchema {
+        meta_schema: generator.settings().meta_schema.clone(),
+        schema: SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(messages),
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+        definitions: generator.take_definitions(),
+    }
+}
+
+/// TypeScript declarations of the types in `schema`, e.g. for frontends that replace the bundled
+/// page.
+#[cfg(feature = "schema")]
+pub fn typescript() -> String {
+    let schema = schema();
+    let mut output = String::from("// Generated by iwgui::protocol::typescript()\n");
+    for (name, definition) in &schema.definitions {
+        output.push('\n');
+        if let Schema::Object(SchemaObject {
+            metadata: Some(metadata),
+            ..
+        }) = definition
+        {
+            if let Some(description) = &metadata.description {
+                output.push_str(&format!("/** {} */\n", description));
+            }
+        }
+        output.push_str(&format!(
+            "export type {} = {};\n",
+            name,
+            typescript_type(definition, 0)
+        ));
+    }
+    output
+}
+
+#[cfg(feature = "schema")]
+fn typescript_type(schema: &Schema, indent: usize) -> String {
+    let object = match schema {
+        Schema::Bool(true) => return "unknown".to_owned(),
+        Schema::Bool(false) => return "never".to_owned(),
+        Schema::Object(object) => object,
+    };
+    if let Some(reference) = &object.reference {
+        return reference.trim_start_matches("#/definitions/").to_owned();
+    }
+    if let Some(value) = &object.const_value {
+        return value.to_string();
+    }
+    if let Some(values) = &object.enum_values {
+        return join(values.iter().map(|v| v.to_string()), " | ");
+    }
+    if let Some(subschemas) = &object.subschemas {
+        if let Some(schemas) = subschemas.one_of.as_ref().or(subschemas.any_of.as_ref()) {
+            return join(schemas.iter().map(|s| typescript_type(s, indent)), " | ");
+        }
+        if let Some(schemas) = &subschemas.all_of {
+            return join(schemas.iter().map(|s| typescript_type(s, indent)), " & ");
+        }
+    }
+    let instance_types = match &object.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => vec![**instance_type],
+        Some(SingleOrVec::Vec(instance_types)) => instance_types.clone(),
+        None => return "unknown".to_owned(),
+    };
+    let types = instance_types
+        .into_iter()
+        .map(|instance_type| match instance_type {
+            InstanceType::Null => "null".to_owned(),
+            InstanceType::Boolean => "boolean".to_owned(),
+            InstanceType::Integer | InstanceType::Number => "number".to_owned(),
+            InstanceType::String => "string".to_owned(),
+            InstanceType::Array => match object.array.as_ref().and_then(|a| a.items.as_ref()) {
+                Some(SingleOrVec::Single(item)) => {
+                    let item = typescript_type(item, indent);
+                    if item.contains(" | ") || item.contains(" & ") {
+                        format!("({})[]", item)
+                    } else {
+                        format!("{}[]", item)
+                    }
+                }
+                Some(SingleOrVec::Vec(items)) => format!(
+                    "[{}]",
+                    join(items.iter().map(|s| typescript_type(s, indent)), ", ")
+                ),
+                None => "unknown[]".to_owned(),
+            },
+            InstanceType::Object => typescript_object(object, indent),
+        });
+    join(types, " | ")
+}
+
+#[cfg(feature = "schema")]
+fn typescript_object(object: &SchemaObject, indent: usize) -> String {
+    let validation = match &object.object {
+        Some(validation) => validation,
+        None => return "{ [key: string]: unknown }".to_owned(),
+    };
+    if validation.properties.is_empty() {
+        let value = match &validation.additional_properties {
+            Some(schema) => typescript_type(schema, indent),
+            None => "unknown".to_owned(),
+        };
+        return format!("{{ [key: string]: {} }}", value);
+    }
+    let mut output = String::from("{\n");
+    for (name, schema) in &validation.properties {
+        let optional = if validation.required.contains(name) {
+            ""
+        } else {
+            "?"
+        };
+        output.push_str(&format!(
+            "{:indent$}{}{}: {};\n",
+            "",
+            name,
+            optional,
+            typescript_type(schema, indent + 4),
+            indent = indent + 4
+        ));
+    }
+    output.push_str(&format!("{:indent$}}}", "", indent = indent));
+    output
+}
+
+#[cfg(feature = "schema")]
+fn join<I: Iterator<Item = String>>(parts: I, separator: &str) -> String {
+    parts.collect::<Vec<_>>().join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminalOutput;
+
+    fn round_trip(previous: &Element, current: &Element) -> Append {
+        let append = Append::between(previous, current).expect("append");
+        let mut element = previous.clone();
+        append.apply(&mut element);
+        assert_eq!(&element, current);
+        append
+    }
+
+    fn terminal(output: &TerminalOutput) -> Element {
+        Element::Terminal {
+            first_line: output.first_line(),
+            lines: output.lines(),
+            prompt: None,
+            attributes: Attributes::default(),
+        }
+    }
+
+    fn log_view(lines: &[&str]) -> Element {
+        Element::LogView {
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            follow: true,
+            attributes: Attributes::default(),
+        }
+    }
+
+    fn line_plot(xs: std::ops::Range<u32>) -> Element {
+        Element::LinePlot {
+            series: vec![PlotSeries {
+                label: "y".to_owned(),
+                points: xs.map(|x| (x as f64, (x * x) as f64)).collect(),
+            }],
+            attributes: Attributes::default(),
+        }
+    }
+
+    #[test]
+    fn overlap_finds_the_longest_kept_end() {
+        assert_eq!(overlap::<u8>(&[], &[]), 0);
+        assert_eq!(overlap(&[1, 2, 3], &[]), 0);
+        assert_eq!(overlap(&[1, 2, 3], &[1, 2, 3, 4]), 3);
+        assert_eq!(overlap(&[1, 2, 3], &[2, 3, 4]), 2);
+        assert_eq!(overlap(&[1, 2, 3], &[4, 5]), 0);
+        assert_eq!(overlap(&[1, 1, 1], &[1, 1, 2]), 2);
+        assert_eq!(overlap(&[1, 2, 1, 2], &[1, 2, 1, 2, 1]), 4);
+        assert_eq!(overlap(&[1, 2, 1, 2], &[1, 2]), 2);
+    }
+
+    #[test]
+    fn terminal_appends_and_rewrites_the_last_line() {
+        let mut output = TerminalOutput::default();
+        output.write("one\ntw");
+        let previous = terminal(&output);
+        output.write("o\nthree\n");
+        let current = terminal(&output);
+        match round_trip(&previous, &current) {
+            Append::Terminal(append) => {
+                assert_eq!(append.from_line, 1);
+                assert_eq!(append.lines.len(), 2);
+            }
+            append => panic!("unexpected {:?}", append),
+        }
+    }
+
+    #[test]
+    fn terminal_drops_lines_beyond_the_scrollback() {
+        let mut output = TerminalOutput::new(4);
+        output.write("a\nb\n\x1b[3");
+        let previous = terminal(&output);
+        output.write("2mc\x1b[0m\nd\ne\nf\n");
+        let current = terminal(&output);
+        round_trip(&previous, &current);
+        output.clear();
+        output.write_line("g");
+        round_trip(&current, &terminal(&output));
+    }
+
+    #[test]
+    fn log_view_slides() {
+        round_trip(&log_view(&["a", "b"]), &log_view(&["a", "b", "c"]));
+        round_trip(&log_view(&["a", "b", "c"]), &log_view(&["c", "d", "e"]));
+        round_trip(&log_view(&["a", "b"]), &log_view(&["x"]));
+        round_trip(&log_view(&["a", "a"]), &log_view(&["a", "a", "a"]));
+        match round_trip(&log_view(&["a", "b", "c"]), &log_view(&["b", "c", "d"])) {
+            Append::LogView(append) => assert_eq!((append.dropped, append.lines.len()), (1, 1)),
+            append => panic!("unexpected {:?}", append),
+        }
+    }
+
+    #[test]
+    fn line_plot_slides() {
+        round_trip(&line_plot(0..10), &line_plot(0..12));
+        round_trip(&line_plot(0..10), &line_plot(5..15));
+        round_trip(&line_plot(0..10), &line_plot(20..25));
+        match round_trip(&line_plot(0..1000), &line_plot(1..1001)) {
+            Append::LinePlot(append) => {
+                assert_eq!(append.series[0].dropped, 1);
+                assert_eq!(append.series[0].points, [(1000.0, 1_000_000.0)]);
+            }
+            append => panic!("unexpected {:?}", append),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use bytes::Bytes;
+use log::warn;
+use parking_lot::{const_mutex, Mutex};
+use std::{
+    error::Error,
+    io,
+    sync::{Arc, OnceLock},
+};
+use tokio::runtime::Runtime;
+use webrtc::{
+    api::APIBuilder,
+    data_channel::{data_channel_message::DataChannelMessage, RTCDataChannel},
+    peer_connection::{
+        configuration::RTCConfiguration, peer_connection_state::RTCPeerConnectionState,
+        sdp::session_description::RTCSessionDescription, RTCPeerConnection,
+    },
+};
+
+use crate::{connection::Shared, Socket, Transport, TransportMessage};
+
+/// Peer connections are kept alive until they fail or are closed by the browser.
+static PEER_CONNECTIONS: Mutex<Vec<Arc<RTCPeerConnection>>> = const_mutex(Vec::new());
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("could not create runtime for WebRTC"))
+}
+
+struct DataChannel(Arc<RTCDataChannel>);
+
+impl Transport for DataChannel {
+    fn send(&mut self, message: TransportMessage) -> io::Result<()> {
+        let result = runtime().block_on(async {
+            match message {
+                TransportMessage::Text(text) => self.0.send_text(text).await,
+                TransportMessage::Binary(data) => self.0.send(&Bytes::from(data)).await,
+            }
+        });
+        result
+            .map(|_| ())
+            .map_err(|err| io::Error::other(err.to_string()))
+    }
+}
+
+/// Answers the offer the browser posted to `/webrtc`. The browser opens one data channel per
+/// direction which are then handled like the websockets.
+pub(crate) fn answer(offer: &str, shared: Arc<Shared>) -> Result<String, Box<dyn Error>> {
+    let offer = serde_json::from_str::<RTCSessionDescription>(offer)?;
+    runtime().block_on(async move {
+        let api = APIBuilder::new().build();
+        let peer_connection = Arc::new(api.new_peer_connection(RTCConfiguration::default()).await?);
+        peer_connection.on_data_channel(Box::new(move |channel: Arc<RTCDataChannel>| {
+            let transport = Box::new(DataChannel(channel.clone()));
+            let socket = Mutex::new(Socket::new(shared.clone(), transport));
+            channel.on_message(Box::new(move |message: DataChannelMessage| {
+                match std::str::from_utf8(&message.data) {
+                    Ok(text) if message.is_string => socket.lock().receive(text),
+                    _ if !message.is_string => socket.lock().receive_binary(&message.data),
+                    _ => warn!("Unexpected message on data channel"),
+                }
+                Box::pin(async {})
+            }));
+            Box::pin(async {})
+        }));
+        let weak = Arc::downgrade(&peer_connection);
+        peer_connection.on_peer_connection_state_change(Box::new(
+            move |state: RTCPeerConnectionState| {
+                if let RTCPeerConnectionState::Failed | RTCPeerConnectionState::Closed = state {
+                    PEER_CONNECTIONS
+                        .lock()
+                        .retain(|p| Arc::as_ptr(p) != weak.as_ptr());
+                }
+                Box::pin(async {})
+            },
+        ));
+        peer_connection.set_remote_description(offer).await?;
+        let answer = peer_connection.create_answer(None).await?;
+        let mut gathering_complete = peer_connection.gathering_complete_promise().await;
+        peer_connection.set_local_description(answer).await?;
+        gathering_complete.recv().await;
+        let answer = peer_connection
+            .local_description()
+            .await
+            .ok_or("no local description after gathering")?;
+        PEER_CONNECTIONS.lock().push(peer_connection);
+        Ok(serde_json::to_string(&answer)?)
+    })
+}
@@ -0,0 +1,58 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+use uuid::Uuid;
+
+use crate::{connection::Shared, protocol::Element, HandleHash, Server};
+
+/// State of a connection that outlives a restart of the server.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedSession {
+    pub(crate) state: Option<serde_json::Value>,
+    pub(crate) fragment: String,
+    /// GUI the browser shows, so that it only gets the changes after reconnecting
+    pub(crate) gui: Option<SavedGui>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedGui {
+    pub(crate) root: HandleHash,
+    #[serde(default)]
+    pub(crate) layers: Vec<HandleHash>,
+    #[serde(default)]
+    pub(crate) title: Option<String>,
+    pub(crate) elements: BTreeMap<HandleHash, Element>,
+}
+
+/// Restores the session of a browser that reconnected after a restart.
+pub(crate) fn take_restored(shared: &Shared, uuid: Uuid) -> Option<SavedSession> {
+    shared.restored.lock().remove(&uuid)
+}
+
+impl Server {
+    /// Writes the state set with `Connection::set_session_state` of every connection to `path`,
+    /// e.g. before the server is shut down for an upgrade. With `with_gui` the last GUI is saved
+    /// as well, so that reconnecting browsers don't get the whole GUI again.
+    pub fn save_sessions<P: AsRef<Path>>(&mut self, path: P, with_gui: bool) -> io::Result<()> {
+        let sessions = self
+            .shared
+            .connections
+            .lock()
+            .iter()
+            .map(|connection| (connection.uuid(), connection.save(with_gui)))
+            .collect::<BTreeMap<_, _>>();
+        fs::write(path, serde_json::to_vec(&sessions)?)?;
+        info!("Saved {} sessions", sessions.len());
+        Ok(())
+    }
+
+    /// Reads sessions written by `save_sessions`. They are given back to the connections when
+    /// their browsers reconnect, which the page does on its own. Returns the number of sessions.
+    pub fn restore_sessions<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+        let sessions: BTreeMap<Uuid, SavedSession> = serde_json::from_slice(&fs::read(path)?)?;
+        let count = sessions.len();
+        self.shared.restored.lock().extend(sessions);
+        info!("Restored {} sessions", count);
+        Ok(count)
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::VecDeque;
+
+pub use crate::protocol::{TerminalLine, TerminalSpan};
+
+#[derive(Default, PartialEq, Clone, Copy)]
+struct Style {
+    fg: Option<u8>,
+    bg: Option<u8>,
+    bold: bool,
+}
+
+/// Lines a terminal keeps by default.
+pub const DEFAULT_SCROLLBACK: usize = 1000;
+
+/// Output of a terminal element, e.g. of a child process. Colors and bold text of ANSI escape
+/// sequences are kept, other sequences are dropped. Only the last lines up to the scrollback
+/// are kept and only the lines that changed are sent to the browser.
+pub struct TerminalOutput {
+    /// The last line is the one that is written to
+    lines: VecDeque<TerminalLine>,
+    /// Number of the first line in `lines` since the output was created
+    first_line: u64,
+    scrollback: usize,
+    style: Style,
+    /// Escape sequence that wasn't terminated yet
+    escape: Option<String>,
+    carriage_return: bool,
+}
+
+impl Default for TerminalOutput {
+    fn default() -> Self {
+        Self::new(DEFAULT_SCROLLBACK)
+    }
+}
+
+impl TerminalOutput {
+    /// Keeps the last `scrollback` lines.
+    pub fn new(scrollback: usize) -> Self {
+        TerminalOutput {
+            lines: VecDeque::from(vec![Vec::new()]),
+            first_line: 0,
+            scrollback: scrollback.max(1),
+            style: Style::default(),
+            escape: None,
+            carriage_return: false,
+        }
+    }
+
+    /// Appends `text`. Escape sequences may be split over several calls. A carriage return
+    /// that isn't followed by a line feed starts the line over, like progress bars expect.
+    pub fn write(&mut self, text: &str) {
+        for c in text.chars() {
+            if let Some(escape) = &mut self.escape {
+                escape.push(c);
+                let finished = match escape.as_bytes() {
+                    // Control sequence, e.g. colors
+                    [b'[', .., last] => (b'@'..=b'~').contains(last),
+                    [b'['] => false,
+                    // Operating system command, e.g. the window title
+                    [b']', .., b'\x07'] | [b']', .., b'\x1b', b'\\'] => true,
+                    [b']', ..] => false,
+                    _ => true,
+                };
+                if finished {
+                    let escape = self.escape.take().unwrap_or_default();
+                    if let Some(parameters) = escape
+                        .strip_prefix('[')
+                        .and_then(|escape| escape.strip_suffix('m'))
+                    {
+                        self.select_graphic_rendition(parameters);
+                    }
+                }
+                continue;
+            }
+            if self.carriage_return && c != '\n' {
+                self.current_line().clear();
+            }
+            self.carriage_return = false;
+            match c {
+                '\x1b' => self.escape = Some(String::new()),
+                '\r' => self.carriage_return = true,
+                '\n' => self.new_line(),
+                c if c.is_control() && c != '\t' => {}
+                c => self.push_char(c),
+            }
+        }
+    }
+
+    /// Appends `line` and a line break.
+    pub fn write_line(&mut self, line: &str) {
+        self.write(line);
+        self.write("\n");
+    }
+
+    pub fn clear(&mut self) {
+        self.first_line += self.lines.len() as u64;
+        self.lines = VecDeque::from(vec![Vec::new()]);
+        self.style = Style::default();
+    }
+
+    pub(crate) fn first_line(&self) -> u64 {
+        self.first_line
+    }
+
+    /// Lines without the last one when it is still empty
+    pub(crate) fn lines(&self) -> Vec<TerminalLine> {
+        let count = match self.lines.back() {
+            Some(last) if last.is_empty() => self.lines.len() - 1,
+            _ => self.lines.len(),
+        };
+        self.lines.iter().take(count).cloned().collect()
+    }
+
+    fn current_line(&mut self) -> &mut TerminalLine {
+        self.lines.back_mut().expect("there is always a line")
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push_back(Vec::new());
+        while self.lines.len() > self.scrollback {
+            self.lines.pop_front();
+            self.first_line += 1;
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        let style = self.style;
+        let line = self.current_line();
+        match line.last_mut() {
+            Some(span) if span.fg == style.fg && span.bg == style.bg && span.bold == style.bold => {
+                span.text.push(c)
+            }
+            _ => line.push(TerminalSpan {
+                text: c.to_string(),
+                fg: style.fg,
+                bg: style.bg,
+                bold: style.bold,
+            }),
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, parameters: &str) {
+        let parameters = parameters
+            .split(';')
+            .map(|parameter| parameter.parse::<u16>().unwrap_or(0))
+            .collect::<Vec<_>>();
+        let mut parameters = parameters.iter().copied();
+        while let Some(parameter) = parameters.next() {
+            match parameter {
+                0 => self.style = Style::default(),
+                1 => self.style.bold = true,
+                22 => self.style.bold = false,
+                30..=37 => self.style.fg = Some((parameter - 30) as u8),
+                39 => self.style.fg = None,
+                40..=47 => self.style.bg = Some((parameter - 40) as u8),
+                49 => self.style.bg = None,
+                90..=97 => self.style.fg = Some((parameter - 90 + 8) as u8),
+                100..=107 => self.style.bg = Some((parameter - 100 + 8) as u8),
+                38 | 48 => {
+                    // 256 colors are kept, true colors are dropped
+                    let color = match parameters.next() {
+                        Some(5) => parameters.next().map(|color| color as u8),
+                        Some(2) => {
+                            parameters.by_ref().take(3).for_each(drop);
+                            None
+                        }
+                        _ => None,
+                    };
+                    if parameter == 38 {
+                        self.style.fg = color;
+                    } else {
+                        self.style.bg = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &TerminalLine) -> String {
+        line.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn colors_become_spans() {
+        let mut output = TerminalOutput::default();
+        output.write_line("a\x1b[1;31mb\x1b[0mc");
+        let lines = output.lines();
+        assert_eq!(lines.len(), 1);
+        let spans = &lines[0];
+        assert_eq!(spans.len(), 3);
+        assert_eq!((spans[0].fg, spans[0].bold), (None, false));
+        assert_eq!(
+            (spans[1].text.as_str(), spans[1].fg, spans[1].bold),
+            ("b", Some(1), true)
+        );
+        assert_eq!((spans[2].fg, spans[2].bold), (None, false));
+    }
+
+    #[test]
+    fn escape_sequences_may_be_split() {
+        let mut whole = TerminalOutput::default();
+        whole.write("x\x1b[38;5;208my\x1b]0;title\x07z\n");
+        let mut split = TerminalOutput::default();
+        for part in ["x\x1b", "[38;", "5;208", "my\x1b]0;ti", "tle\x07", "z\n"] {
+            split.write(part);
+        }
+        assert_eq!(split.lines(), whole.lines());
+        assert_eq!(text(&whole.lines()[0]), "xyz");
+        assert_eq!(whole.lines()[0][1].fg, Some(208));
+    }
+
+    #[test]
+    fn carriage_return_starts_the_line_over() {
+        let mut output = TerminalOutput::default();
+        output.write("10%\r50%\r100%\r\n");
+        assert_eq!(text(&output.lines()[0]), "100%");
+    }
+
+    #[test]
+    fn scrollback_drops_the_oldest_lines() {
+        let mut output = TerminalOutput::new(3);
+        for i in 0..5 {
+            output.write_line(&i.to_string());
+        }
+        let lines = output.lines().iter().map(text).collect::<Vec<_>>();
+        assert_eq!(lines, ["3", "4"]);
+        assert_eq!(output.first_line(), 3);
+    }
+}
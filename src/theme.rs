@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Colors, font and spacing of the page. They are sent to the browser as CSS variables (e.g.
+/// `--background`), so user stylesheets can use them as well.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Background of the page
+    pub background: String,
+    pub text: String,
+    /// Text that is less important, e.g. placeholders and axis labels
+    pub text_muted: String,
+    /// Background of elements that float above the page, like overlays and windows
+    pub surface: String,
+    /// Background of inputs and headers
+    pub surface_muted: String,
+    pub border: String,
+    /// Background of buttons and selected items
+    pub interactive: String,
+    pub interactive_hover: String,
+    /// Text on top of `interactive`
+    pub interactive_text: String,
+    /// Highlight of invalid inputs
+    pub error: String,
+    /// CSS font family, e.g. `"'Abel', sans-serif"`
+    pub font_family: String,
+    /// Font size in CSS pixels
+    pub font_size: f64,
+    /// Base distance between elements in CSS pixels
+    pub spacing: f64,
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            background: "#FFFFFF".to_owned(),
+            text: "#000000".to_owned(),
+            text_muted: "#888888".to_owned(),
+            surface: "#FFFFFF".to_owned(),
+            surface_muted: "#EEEEEE".to_owned(),
+            border: "#CCCCCC".to_owned(),
+            interactive: "#1e92fe".to_owned(),
+            interactive_hover: "#1074e0".to_owned(),
+            interactive_text: "#FFFFFF".to_owned(),
+            error: "#D32F2F".to_owned(),
+            font_family: "'Abel', sans-serif".to_owned(),
+            font_size: 16.0,
+            spacing: 4.0,
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background: "#1E1E1E".to_owned(),
+            text: "#E4E4E4".to_owned(),
+            text_muted: "#8C8C8C".to_owned(),
+            surface: "#2A2A2A".to_owned(),
+            surface_muted: "#333333".to_owned(),
+            border: "#4A4A4A".to_owned(),
+            interactive: "#1e92fe".to_owned(),
+            interactive_hover: "#4aa8ff".to_owned(),
+            interactive_text: "#FFFFFF".to_owned(),
+            error: "#F2706B".to_owned(),
+            ..Self::light()
+        }
+    }
+
+    /// CSS variables that are set on the root of the page
+    pub(crate) fn css_variables(&self) -> BTreeMap<String, String> {
+        let variables = [
+            ("--background", self.background.clone()),
+            ("--text", self.text.clone()),
+            ("--text-muted", self.text_muted.clone()),
+            ("--surface", self.surface.clone()),
+            ("--surface-muted", self.surface_muted.clone()),
+            ("--border", self.border.clone()),
+            ("--interactive", self.interactive.clone()),
+            ("--interactive-hover", self.interactive_hover.clone()),
+            ("--interactive-text", self.interactive_text.clone()),
+            ("--error", self.error.clone()),
+            ("--font-family", self.font_family.clone()),
+            ("--font-size", format!("{}px", self.font_size)),
+            ("--spacing", format!("{}px", self.spacing)),
+        ];
+        variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
@@ -0,0 +1,33 @@
+use parking_lot::Mutex;
+use std::thread;
+
+use crate::{Connection, Server};
+
+impl Server {
+    /// Runs `build` for every connection like a loop over `connections` does, but spreads the
+    /// connections over `threads` worker threads. `model` is locked while `build` runs. Diffing
+    /// and serializing the GUI passed to `Connection::show_gui` happens after the model was
+    /// unlocked, so the connections only wait for each other while the GUI is built. Updates for
+    /// which `show_gui` would return `LimitExceeded` are logged and dropped.
+    pub fn build_in_parallel<M, F>(&mut self, threads: usize, model: &mut M, build: F)
+    where
+        M: Send,
+        F: Fn(&mut Connection, &mut M) + Sync,
+    {
+        let mut connections = self.connections();
+        let jobs = Mutex::new((&mut connections).into_iter());
+        let model = Mutex::new(model);
+        thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(|| loop {
+                    let Some(connection) = jobs.lock().next() else {
+                        break;
+                    };
+                    connection.defer_show_gui();
+                    build(connection, &mut model.lock());
+                    connection.show_deferred_gui();
+                });
+            }
+        });
+    }
+}